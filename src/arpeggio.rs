@@ -0,0 +1,110 @@
+//! Contains the state that channels use to cycle through the pitches of an arpeggio.
+
+use crate::errors::{LSynthError, InvalidArpeggioRateError};
+
+/// The maximum number of steps in an arpeggio.
+pub const ARPEGGIO_LENGTH: usize = 8;
+
+/// The pitch offsets of each step of an arpeggio in semitones. This is a convenience type for arrays that follow the required pattern.
+pub type ArpeggioOffsets = [f32; ARPEGGIO_LENGTH];
+
+/// How an arpeggio decides when to move to its next step.
+#[derive(Clone, Copy)]
+enum ArpeggioTiming {
+	/// Steps once every this many ticks of the chip.
+	Ticks(usize),
+	/// Steps at this rate in hertz.
+	Rate(f32),
+}
+
+/// The state of an arpeggio being played by a channel.
+pub(crate) struct Arpeggio {
+	/// The pitch offsets of each step in semitones.
+	offsets: ArpeggioOffsets,
+	/// How many of the offsets are used. An arpeggio with no steps has no effect.
+	length: usize,
+	/// The index of the offset currently being played.
+	step: usize,
+	/// How the arpeggio decides when to move to its next step.
+	timing: ArpeggioTiming,
+	/// The progress towards the next step. Measured in ticks or on a scale of 0..1 depending on the timing.
+	progress: f32,
+}
+
+impl Arpeggio {
+	/// Creates a new arpeggio with no steps.
+	pub(crate) fn new() -> Arpeggio {
+		Arpeggio {
+			offsets: [0.0; ARPEGGIO_LENGTH],
+			length: 0,
+			step: 0,
+			timing: ArpeggioTiming::Ticks(1),
+			progress: 0.0,
+		}
+	}
+	
+	/// Replaces the arpeggio with one that steps once every `ticks` ticks, starting from its first step.
+	pub(crate) fn set_ticks(&mut self, offsets: ArpeggioOffsets, length: usize, ticks: usize) {
+		self.set(offsets, length, ArpeggioTiming::Ticks(ticks.max(1)));
+	}
+	
+	/// Replaces the arpeggio with one that steps at the given rate in hertz, starting from its first step.
+	/// Returns an error, leaving the arpeggio untouched, if the rate isn't finite.
+	pub(crate) fn set_rate(&mut self, offsets: ArpeggioOffsets, length: usize, rate: f32) -> Result<(), LSynthError> {
+		if !rate.is_finite() {
+			return Err(LSynthError::InvalidArpeggioRate(InvalidArpeggioRateError {
+				attempted_rate: rate,
+			}));
+		}
+		self.set(offsets, length, ArpeggioTiming::Rate(rate.max(0.0)));
+		Ok(())
+	}
+	
+	/// Replaces the arpeggio, starting from its first step.
+	fn set(&mut self, offsets: ArpeggioOffsets, length: usize, timing: ArpeggioTiming) {
+		self.offsets = offsets;
+		self.length = length.min(ARPEGGIO_LENGTH);
+		self.timing = timing;
+		self.step = 0;
+		self.progress = 0.0;
+	}
+	
+	/// The amount that the frequency of the channel is multiplied by at the current step.
+	pub(crate) fn multiplier(&self) -> f32 {
+		if self.length == 0 { return 1.0; }
+		(self.offsets[self.step] / 12.0).exp2()
+	}
+	
+	/// Informs the arpeggio that a tick of the chip has been completed.
+	pub(crate) fn tick(&mut self) {
+		if let ArpeggioTiming::Ticks(ticks) = self.timing {
+			self.progress += 1.0;
+			if self.progress >= ticks as f32 {
+				self.progress = 0.0;
+				self.next_step();
+			}
+		}
+	}
+	
+	/// Updates the state of the arpeggio by the provided timestep in seconds.
+	pub(crate) fn advance(&mut self, step: f32) {
+		if let ArpeggioTiming::Rate(rate) = self.timing {
+			self.progress += rate * step;
+			if self.progress >= 1.0 {
+				let steps = self.progress.floor();
+				self.progress -= steps;
+				if self.length > 0 {
+					// Only the position within the arpeggio matters, however many times it has looped.
+					let steps = (steps % self.length as f32) as usize;
+					self.step = (self.step + steps) % self.length;
+				}
+			}
+		}
+	}
+	
+	/// Moves on to the next step, looping back to the first step after the last one.
+	fn next_step(&mut self) {
+		if self.length == 0 { return; }
+		self.step = (self.step + 1) % self.length;
+	}
+}
@@ -1,6 +1,7 @@
 //! Provides C compatible functions for working with this library as a DLL.
 
-use crate::{ChipState, ChipParameters, Command, ChipGenerationData};
+use crate::{ChipState, ChipParameters, Command, MasterCommand, DcBlocking, Ramping, events::ChipEvent, ChipGenerationData, format::OutputSample, arpeggio::{ArpeggioOffsets, ARPEGGIO_LENGTH}, pcm::PcmSample, eq::EqBand, waveform::HARMONIC_COUNT};
+use std::ffi::c_void;
 
 /// Initiates a new LSynth chip
 #[no_mangle]
@@ -8,6 +9,12 @@ pub extern "C" fn ls_init(channel_count: usize, samplerate: usize, amplitude: f3
 	Box::into_raw(Box::new(ChipState::new(channel_count, ChipParameters::new(samplerate, amplitude, tick_rate))))
 }
 
+/// Identical to ls_init, but takes the tick rate in double precision, so that rates like 59.94 don't drift.
+#[no_mangle]
+pub extern "C" fn ls_init_f64(channel_count: usize, samplerate: usize, amplitude: f32, tick_rate: f64) -> *mut ChipState {
+	Box::into_raw(Box::new(ChipState::new(channel_count, ChipParameters::new_f64(samplerate, amplitude, tick_rate))))
+}
+
 /// Generates audio with the provided chip.
 /// # Safety
 /// chip_state must be a valid ChipState generated from the ls_init function.
@@ -21,6 +28,248 @@ pub unsafe extern "C" fn ls_generate(chip_state: *mut ChipState, buffer_ptr: *mu
 	chip_state.generate(&mut buffer[buffer_start..]).unwrap()
 }
 
+/// Generates audio in the given output format. Shared by the format specific generation functions.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// 
+/// buffer_ptr must point to the first element in an array, and buffer_len must be the length of that array.
+unsafe fn generate_as<T: OutputSample>(chip_state: *mut ChipState, buffer_ptr: *mut T, buffer_len: usize, buffer_start: usize) -> ChipGenerationData {
+	let chip_state = &mut *chip_state;
+	let buffer = std::slice::from_raw_parts_mut(buffer_ptr, buffer_len);
+	
+	chip_state.generate_as(&mut buffer[buffer_start..]).unwrap()
+}
+
+/// Generates audio with the provided chip as signed 16 bit integers.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// 
+/// buffer_ptr must point to the first i16 in an array, and buffer_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_generate_i16(chip_state: *mut ChipState, buffer_ptr: *mut i16, buffer_len: usize, buffer_start: usize) -> ChipGenerationData {
+	generate_as(chip_state, buffer_ptr, buffer_len, buffer_start)
+}
+
+/// Generates audio with the provided chip as unsigned 8 bit integers, centered on 128.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// 
+/// buffer_ptr must point to the first u8 in an array, and buffer_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_generate_u8(chip_state: *mut ChipState, buffer_ptr: *mut u8, buffer_len: usize, buffer_start: usize) -> ChipGenerationData {
+	generate_as(chip_state, buffer_ptr, buffer_len, buffer_start)
+}
+
+/// Generates audio with the provided chip as signed 32 bit integers.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// 
+/// buffer_ptr must point to the first i32 in an array, and buffer_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_generate_i32(chip_state: *mut ChipState, buffer_ptr: *mut i32, buffer_len: usize, buffer_start: usize) -> ChipGenerationData {
+	generate_as(chip_state, buffer_ptr, buffer_len, buffer_start)
+}
+
+/// Generates audio with the provided chip as 64 bit floats.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// 
+/// buffer_ptr must point to the first f64 in an array, and buffer_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_generate_f64(chip_state: *mut ChipState, buffer_ptr: *mut f64, buffer_len: usize, buffer_start: usize) -> ChipGenerationData {
+	generate_as(chip_state, buffer_ptr, buffer_len, buffer_start)
+}
+
+/// Sets whether the provided chip queues events while generating audio. Events are not queued by default.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_events_enabled(chip_state: *mut ChipState, events_enabled: bool) {
+	let chip_state = &mut *chip_state;
+	chip_state.parameters.set_events_enabled(events_enabled);
+}
+
+/// Takes the oldest event reported while the provided chip was generating audio, writing it to event.
+/// Returns false, leaving event untouched, if there are no events waiting.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// event must point to a valid ChipEvent.
+#[no_mangle]
+pub unsafe extern "C" fn ls_poll_event(chip_state: *mut ChipState, event: *mut ChipEvent) -> bool {
+	let chip_state = &mut *chip_state;
+	match chip_state.poll_event() {
+		Some(polled) => {
+			*event = polled;
+			true
+		}
+		None => false,
+	}
+}
+
+/// Returns how many events are waiting to be taken from the provided chip with ls_poll_event.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_event_count(chip_state: *mut ChipState) -> usize {
+	let chip_state = &*chip_state;
+	chip_state.event_count()
+}
+
+/// Returns how many events the provided chip has discarded because its queue was full.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_dropped_event_count(chip_state: *mut ChipState) -> u64 {
+	let chip_state = &*chip_state;
+	chip_state.dropped_event_count()
+}
+
+/// Fills the whole of the provided array with audio generated by the provided chip,
+/// calling on_tick with the chip, the index of the completed tick and user_data every time a tick is completed.
+/// on_tick may be null, in which case the ticks are not reported.
+/// Returns false if the array has an odd length.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// 
+/// buffer_ptr must point to the first f32 in an array, and buffer_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_render_with_ticks(chip_state: *mut ChipState, buffer_ptr: *mut f32, buffer_len: usize, on_tick: Option<extern "C" fn(*mut ChipState, u64, *mut c_void)>, user_data: *mut c_void) -> bool {
+	let chip_state = &mut *chip_state;
+	let buffer = std::slice::from_raw_parts_mut(buffer_ptr, buffer_len);
+	
+	chip_state.render_with_ticks(buffer, &mut |chip: &mut ChipState, tick_index| {
+		if let Some(on_tick) = on_tick {
+			on_tick(chip, tick_index, user_data);
+		}
+	}).is_ok()
+}
+
+/// Sets the swing of the provided chip's ticks, as a percentage of each pair of ticks taken by the first tick.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_swing(chip_state: *mut ChipState, swing: f32) {
+	let chip_state = &mut *chip_state;
+	chip_state.parameters.set_swing(swing);
+}
+
+/// Sets a repeating pattern of tick lengths for the provided chip, relative to the length set by the tick rate.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// groove_ptr must point to an array of at least groove_len f32s.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_groove(chip_state: *mut ChipState, groove_ptr: *const f32, groove_len: usize) {
+	let chip_state = &mut *chip_state;
+	let groove = if groove_len == 0 { &[] } else { std::slice::from_raw_parts(groove_ptr, groove_len) };
+	chip_state.parameters.set_groove(groove);
+}
+
+/// Sets where DC offset is removed from the output of the chip.
+/// 0 is off, 1 removes it from each channel, and 2 removes it from the mixed output.
+/// Returns false if dc_blocking is out of range.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_dc_blocking(chip_state: *mut ChipState, dc_blocking: usize) -> bool {
+	let chip_state = &mut *chip_state;
+	let dc_blocking = match dc_blocking {
+		0 => DcBlocking::Off,
+		1 => DcBlocking::Channels,
+		2 => DcBlocking::Master,
+		_ => return false,
+	};
+	chip_state.parameters.set_dc_blocking(dc_blocking);
+	true
+}
+
+/// Sets the low shelf of the parametric EQ applied to the output of the chip.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_eq_low_shelf(chip_state: *mut ChipState, band: EqBand) {
+	let chip_state = &mut *chip_state;
+	let mut eq = chip_state.parameters.get_eq().clone();
+	eq.low_shelf = band;
+	chip_state.parameters.set_eq(eq);
+}
+
+/// Sets the high shelf of the parametric EQ applied to the output of the chip.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_eq_high_shelf(chip_state: *mut ChipState, band: EqBand) {
+	let chip_state = &mut *chip_state;
+	let mut eq = chip_state.parameters.get_eq().clone();
+	eq.high_shelf = band;
+	chip_state.parameters.set_eq(eq);
+}
+
+/// Replaces the peak bands of the parametric EQ applied to the output of the chip with the provided array of bands.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// bands must point to an array of at least band_count bands.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_eq_peaks(chip_state: *mut ChipState, bands: *const EqBand, band_count: usize) {
+	let chip_state = &mut *chip_state;
+	let bands = if band_count == 0 { &[] } else { std::slice::from_raw_parts(bands, band_count) };
+	let mut eq = chip_state.parameters.get_eq().clone();
+	eq.peaks = bands.to_vec();
+	chip_state.parameters.set_eq(eq);
+}
+
+/// Sets how quickly the amplitude and panning of the chip's channels follow changes.
+/// A kind of 0 ramps at a rate in units/second, and 1 ramps over a time in milliseconds.
+/// Returns false if the kind is out of range.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_ramping(chip_state: *mut ChipState, kind: usize, value: f32) -> bool {
+	let chip_state = &mut *chip_state;
+	match ramping(kind, value) {
+		Some(ramping) => {
+			chip_state.parameters.set_ramping(ramping);
+			true
+		}
+		None => false,
+	}
+}
+
+/// Generates audio with the provided chip, writing the left and right channels to separate arrays.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// 
+/// left_ptr and right_ptr must each point to the first f32 in an array, and buffer_len must be the length of both arrays.
+#[no_mangle]
+pub unsafe extern "C" fn ls_generate_planar(chip_state: *mut ChipState, left_ptr: *mut f32, right_ptr: *mut f32, buffer_len: usize, buffer_start: usize) -> ChipGenerationData {
+	let chip_state = &mut *chip_state;
+	let left = std::slice::from_raw_parts_mut(left_ptr, buffer_len);
+	let right = std::slice::from_raw_parts_mut(right_ptr, buffer_len);
+	
+	chip_state.generate_planar(&mut left[buffer_start..], &mut right[buffer_start..]).unwrap()
+}
+
+/// Generates audio with the provided chip, downmixed to a single channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// 
+/// buffer_ptr must point to the first f32 in an array, and buffer_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_generate_mono(chip_state: *mut ChipState, buffer_ptr: *mut f32, buffer_len: usize, buffer_start: usize) -> ChipGenerationData {
+	let chip_state = &mut *chip_state;
+	let buffer = std::slice::from_raw_parts_mut(buffer_ptr, buffer_len);
+	
+	chip_state.generate_mono(&mut buffer[buffer_start..]).unwrap()
+}
+
+/// Sets whether audio generated in integer formats is dithered.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_dither(chip_state: *mut ChipState, dither: bool) {
+	let chip_state = &mut *chip_state;
+	chip_state.parameters.set_dither(dither);
+}
+
 /// Inserts a command into the provided chip_state
 /// # Safety
 /// chip_state must be a valid ChipState generated from the ls_init function.
@@ -31,6 +280,16 @@ pub unsafe extern "C" fn ls_send_command(chip_state: *mut ChipState, command: Co
 	let _ = chip_state.send_command(command, channel);
 }
 
+/// Inserts a command into the effects applied to the output of the provided chip_state
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_send_master_command(chip_state: *mut ChipState, command: MasterCommand) {
+	let chip_state = &mut *chip_state;
+	
+	let _ = chip_state.send_master_command(command);
+}
+
 /// Returns the number of samples that are in a single tick.
 /// # Safety
 /// chip_state must be a valid ChipState generated from the ls_init function.
@@ -40,6 +299,15 @@ pub unsafe extern "C" fn ls_get_tick_frames(chip_state: *mut ChipState) -> f32 {
 	chip_state.parameters.get_tick_frames()
 }
 
+/// Returns the number of samples that are in a single tick, in the double precision used to place the ticks.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_get_tick_frames_f64(chip_state: *mut ChipState) -> f64 {
+	let chip_state = & *chip_state;
+	chip_state.parameters.get_tick_frames_f64()
+}
+
 /// Sends a SetWaveform command to the given channel.
 /// # Safety
 /// chip_state must be a valid ChipState generated from the ls_init function.
@@ -76,17 +344,54 @@ pub unsafe extern "C" fn ls_set_panning(chip_state: *mut ChipState, channel: usi
 	let _ = chip_state.send_command(Command::SetPanning(panning), channel);
 }
 
-/// Sends a SetCustomWaveform command to the given channel.
+/// Changes the custom waveform of the given channel. The waveform can be any power of two length supported by `ChipState::set_custom_waveform`.
+/// Waveforms that can't be set are ignored. Use ls_try_set_custom_waveform to find out whether the waveform was set.
 /// # Safety
 /// chip_state must be a valid ChipState generated from the ls_init function.
 /// waveform_ptr must point to the first f32 in an array, and waveform_len must be the length of that array.
 #[no_mangle]
 pub unsafe extern "C" fn ls_set_custom_waveform(chip_state: *mut ChipState, channel: usize, waveform_ptr: *mut f32, waveform_len: usize) {
+	let _ = ls_try_set_custom_waveform(chip_state, channel, waveform_ptr, waveform_len);
+}
+
+/// Identical to ls_set_custom_waveform, but returns false if the waveform could not be set.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// waveform_ptr must point to the first f32 in an array, and waveform_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_try_set_custom_waveform(chip_state: *mut ChipState, channel: usize, waveform_ptr: *mut f32, waveform_len: usize) -> bool {
 	let chip_state = &mut *chip_state;
+	if waveform_len == 0 { return false; }
 	
-	let mut waveform = [0.0; crate::waveform::CUSTOM_WIDTH];
-	waveform.clone_from_slice(std::slice::from_raw_parts(waveform_ptr, waveform_len));
-	let _ = chip_state.send_command(Command::SetCustomWaveform(waveform), channel);
+	let waveform = std::slice::from_raw_parts(waveform_ptr, waveform_len);
+	chip_state.set_custom_waveform(waveform, channel).is_ok()
+}
+
+/// Sends a SetHarmonics command to the given channel. Harmonics past the end of the arrays are silent.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// amplitudes_ptr and phases_ptr must each point to the first f32 in an array, and harmonics_len must be the length of both arrays.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_harmonics(chip_state: *mut ChipState, channel: usize, amplitudes_ptr: *const f32, phases_ptr: *const f32, harmonics_len: usize) {
+	let chip_state = &mut *chip_state;
+	let length = harmonics_len.min(HARMONIC_COUNT);
+	
+	let mut amplitudes = [0.0; HARMONIC_COUNT];
+	let mut phases = [0.0; HARMONIC_COUNT];
+	if length > 0 {
+		amplitudes[..length].copy_from_slice(std::slice::from_raw_parts(amplitudes_ptr, length));
+		phases[..length].copy_from_slice(std::slice::from_raw_parts(phases_ptr, length));
+	}
+	let _ = chip_state.send_command(Command::SetHarmonics(amplitudes, phases), channel);
+}
+
+/// Sends a SetInterpolation command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_interpolation(chip_state: *mut ChipState, channel: usize, interpolation: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetInterpolation(interpolation), channel);
 }
 
 /// Sends a SetPhase command to the given channel.
@@ -141,4 +446,402 @@ pub unsafe extern "C" fn ls_amplitude_slide(chip_state: *mut ChipState, channel:
 pub unsafe extern "C" fn ls_panning_slide(chip_state: *mut ChipState, channel: usize, panning: f32, rate: f32) {
 	let chip_state = &mut *chip_state;
 	let _ = chip_state.send_command(Command::PanningSlide(panning, rate), channel);
+}
+
+/// Sends a SetPulseWidth command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_pulse_width(chip_state: *mut ChipState, channel: usize, pulse_width: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetPulseWidth(pulse_width), channel);
+}
+
+/// Sends a SetLfo command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_lfo(chip_state: *mut ChipState, channel: usize, target: usize, shape: usize, rate: f32, depth: f32, delay: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetLfo(target, shape, rate, depth, delay), channel);
+}
+
+/// Sends a SetArpeggio command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// offsets_ptr must point to the first f32 in an array, and offsets_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_arpeggio(chip_state: *mut ChipState, channel: usize, offsets_ptr: *const f32, offsets_len: usize, ticks: usize) {
+	let chip_state = &mut *chip_state;
+	let (offsets, length) = arpeggio_offsets(offsets_ptr, offsets_len);
+	let _ = chip_state.send_command(Command::SetArpeggio(offsets, length, ticks), channel);
+}
+
+/// Sends a SetArpeggioRate command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// offsets_ptr must point to the first f32 in an array, and offsets_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_arpeggio_rate(chip_state: *mut ChipState, channel: usize, offsets_ptr: *const f32, offsets_len: usize, rate: f32) {
+	let chip_state = &mut *chip_state;
+	let (offsets, length) = arpeggio_offsets(offsets_ptr, offsets_len);
+	let _ = chip_state.send_command(Command::SetArpeggioRate(offsets, length, rate), channel);
+}
+
+/// Sends a SetFmOperator command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_fm_operator(chip_state: *mut ChipState, channel: usize, operator: usize, ratio: f32, level: f32, shape: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetFmOperator(operator, ratio, level, shape), channel);
+}
+
+/// Sends a SetFmEnvelope command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_fm_envelope(chip_state: *mut ChipState, channel: usize, operator: usize, attack: f32, decay: f32, sustain: f32, release: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetFmEnvelope(operator, attack, decay, sustain, release), channel);
+}
+
+/// Sends a SetFmAlgorithm command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_fm_algorithm(chip_state: *mut ChipState, channel: usize, algorithm: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetFmAlgorithm(algorithm), channel);
+}
+
+/// Sends a SetFmFeedback command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_fm_feedback(chip_state: *mut ChipState, channel: usize, feedback: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetFmFeedback(feedback), channel);
+}
+
+/// Sends an FmKeyOn command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_fm_key_on(chip_state: *mut ChipState, channel: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::FmKeyOn, channel);
+}
+
+/// Sends an FmKeyOff command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_fm_key_off(chip_state: *mut ChipState, channel: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::FmKeyOff, channel);
+}
+
+/// Sends a SetModulation command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_modulation(chip_state: *mut ChipState, channel: usize, mode: usize, source: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetModulation(mode, source), channel);
+}
+
+/// Loads a PCM sample into the chip, returning the index used to refer to it in commands.
+/// The sample loops between loop_start and loop_end, unless they are equal, in which case it plays once.
+/// If the loop points are invalid, the sample is loaded without looping.
+/// Returns -1 if the samplerate is 0, or the base frequency isn't positive and finite.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// data_ptr must point to the first f32 in an array, and data_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_load_pcm_sample(chip_state: *mut ChipState, data_ptr: *const f32, data_len: usize, samplerate: usize, base_frequency: f32, loop_start: usize, loop_end: usize) -> isize {
+	let chip_state = &mut *chip_state;
+	let data = if data_len > 0 { std::slice::from_raw_parts(data_ptr, data_len).to_vec() } else { Vec::new() };
+	
+	let mut sample = match PcmSample::new(data, samplerate, base_frequency) {
+		Ok(sample) => sample,
+		Err(_) => return -1,
+	};
+	if loop_start != loop_end {
+		let _ = sample.set_loop(loop_start, loop_end);
+	}
+	chip_state.load_pcm_sample(sample) as isize
+}
+
+/// Sends a SetPcmSample command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_pcm_sample(chip_state: *mut ChipState, channel: usize, sample: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetPcmSample(sample), channel);
+}
+
+/// Loads a wavetable into the chip's bank, returning the index used to refer to it in commands.
+/// Returns -1 if the wavetable has an unsupported length.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// wavetable_ptr must point to the first f32 in an array, and wavetable_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_load_wavetable(chip_state: *mut ChipState, wavetable_ptr: *const f32, wavetable_len: usize) -> isize {
+	let chip_state = &mut *chip_state;
+	if wavetable_len == 0 { return -1; }
+	
+	match chip_state.load_wavetable(std::slice::from_raw_parts(wavetable_ptr, wavetable_len)) {
+		Ok(index) => index as isize,
+		Err(_) => -1,
+	}
+}
+
+/// Replaces a wavetable in the chip's bank. Returns false if the wavetable could not be replaced.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+/// wavetable_ptr must point to the first f32 in an array, and wavetable_len must be the length of that array.
+#[no_mangle]
+pub unsafe extern "C" fn ls_replace_wavetable(chip_state: *mut ChipState, index: usize, wavetable_ptr: *const f32, wavetable_len: usize) -> bool {
+	let chip_state = &mut *chip_state;
+	if wavetable_len == 0 { return false; }
+	
+	chip_state.replace_wavetable(index, std::slice::from_raw_parts(wavetable_ptr, wavetable_len)).is_ok()
+}
+
+/// Sends a SetWavetables command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_wavetables(chip_state: *mut ChipState, channel: usize, first: usize, second: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetWavetables(first, second), channel);
+}
+
+/// Sends a SetWavetablePosition command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_wavetable_position(chip_state: *mut ChipState, channel: usize, position: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetWavetablePosition(position), channel);
+}
+
+/// Sends a WavetablePositionSlide command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_wavetable_position_slide(chip_state: *mut ChipState, channel: usize, position: f32, rate: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::WavetablePositionSlide(position, rate), channel);
+}
+
+/// Sends a SetReverbSend command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_reverb_send(chip_state: *mut ChipState, channel: usize, send: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetReverbSend(send), channel);
+}
+
+/// Sends a SetBitDepth command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_bit_depth(chip_state: *mut ChipState, channel: usize, bit_depth: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetBitDepth(bit_depth), channel);
+}
+
+/// Sends a BitDepthSlide command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_bit_depth_slide(chip_state: *mut ChipState, channel: usize, bit_depth: f32, rate: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::BitDepthSlide(bit_depth, rate), channel);
+}
+
+/// Sends a SetDecimation command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_decimation(chip_state: *mut ChipState, channel: usize, decimation: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetDecimation(decimation), channel);
+}
+
+/// Sends a DecimationSlide command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_decimation_slide(chip_state: *mut ChipState, channel: usize, decimation: f32, rate: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::DecimationSlide(decimation, rate), channel);
+}
+
+/// Sends a SetDistortion command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_distortion(chip_state: *mut ChipState, channel: usize, distortion: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetDistortion(distortion), channel);
+}
+
+/// Sends a SetDrive command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_drive(chip_state: *mut ChipState, channel: usize, drive: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetDrive(drive), channel);
+}
+
+/// Sends a DriveSlide command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_drive_slide(chip_state: *mut ChipState, channel: usize, drive: f32, rate: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::DriveSlide(drive, rate), channel);
+}
+
+/// Sends a SetDistortionAsymmetry command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_distortion_asymmetry(chip_state: *mut ChipState, channel: usize, asymmetry: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetDistortionAsymmetry(asymmetry), channel);
+}
+
+/// Sends a SetChorus command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_chorus(chip_state: *mut ChipState, channel: usize, rate: f32, depth: f32, mix: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetChorus(rate, depth, mix), channel);
+}
+
+/// Sends a SetStereoWidth command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_stereo_width(chip_state: *mut ChipState, channel: usize, width: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetStereoWidth(width), channel);
+}
+
+/// Sends a SetRamping command to the given channel.
+/// A kind of 0 ramps at a rate in units/second, and 1 ramps over a time in milliseconds.
+/// Returns false if the kind is out of range.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_channel_ramping(chip_state: *mut ChipState, channel: usize, kind: usize, value: f32) -> bool {
+	let chip_state = &mut *chip_state;
+	match ramping(kind, value) {
+		Some(ramping) => {
+			let _ = chip_state.send_command(Command::SetRamping(ramping), channel);
+			true
+		}
+		None => false,
+	}
+}
+
+/// Sends a ResetRamping command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_reset_channel_ramping(chip_state: *mut ChipState, channel: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::ResetRamping, channel);
+}
+
+/// Sends a FrequencySlideTime command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_frequency_slide_time(chip_state: *mut ChipState, channel: usize, frequency: f32, time: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::FrequencySlideTime(frequency, time), channel);
+}
+
+/// Sends an AmplitudeSlideTime command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_amplitude_slide_time(chip_state: *mut ChipState, channel: usize, amplitude: f32, time: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::AmplitudeSlideTime(amplitude, time), channel);
+}
+
+/// Sends a PanningSlideTime command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_panning_slide_time(chip_state: *mut ChipState, channel: usize, panning: f32, time: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::PanningSlideTime(panning, time), channel);
+}
+
+/// Sends a FrequencySlideTicks command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_frequency_slide_ticks(chip_state: *mut ChipState, channel: usize, frequency: f32, ticks: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::FrequencySlideTicks(frequency, ticks), channel);
+}
+
+/// Sends an AmplitudeSlideTicks command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_amplitude_slide_ticks(chip_state: *mut ChipState, channel: usize, amplitude: f32, ticks: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::AmplitudeSlideTicks(amplitude, ticks), channel);
+}
+
+/// Sends a PanningSlideTicks command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_panning_slide_ticks(chip_state: *mut ChipState, channel: usize, panning: f32, ticks: f32) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::PanningSlideTicks(panning, ticks), channel);
+}
+
+/// Sends a SetSlideCurve command to the given channel.
+/// # Safety
+/// chip_state must be a valid ChipState generated from the ls_init function.
+#[no_mangle]
+pub unsafe extern "C" fn ls_set_slide_curve(chip_state: *mut ChipState, channel: usize, target: usize, curve: usize) {
+	let chip_state = &mut *chip_state;
+	let _ = chip_state.send_command(Command::SetSlideCurve(target, curve), channel);
+}
+
+/// Builds a ramping from the kind and value passed to a C function, or returns `None` if the kind is out of range.
+fn ramping(kind: usize, value: f32) -> Option<Ramping> {
+	match kind {
+		0 => Some(Ramping::Rate(value)),
+		1 => Some(Ramping::Time(value)),
+		_ => None,
+	}
+}
+
+/// Copies as many arpeggio offsets as will fit from a C array, returning them along with how many were copied.
+/// # Safety
+/// offsets_ptr must point to the first f32 in an array, and offsets_len must be the length of that array.
+unsafe fn arpeggio_offsets(offsets_ptr: *const f32, offsets_len: usize) -> (ArpeggioOffsets, usize) {
+	let length = offsets_len.min(ARPEGGIO_LENGTH);
+	let mut offsets = [0.0; ARPEGGIO_LENGTH];
+	if length > 0 {
+		offsets[..length].copy_from_slice(std::slice::from_raw_parts(offsets_ptr, length));
+	}
+	(offsets, length)
 }
\ No newline at end of file
@@ -1,22 +1,36 @@
 //! Contains tools for keeping track of the state of individual channels.
 
-use crate::{Command, waveform, errors::*};
+use std::sync::Arc;
+use crate::{Command, Ramping, waveform, errors::*, lfo::*, arpeggio::Arpeggio, slide::*, events::ChannelEvent, fm::*, pcm::*, effects::{bitcrusher::Bitcrusher, chorus::*, waveshaper::*}, ChipParameters};
 
-/// The time it takes for amplitude and panning changes to occur. This prevents clicks from abrupt changes.
+/// The default rate in units/second at which amplitude and panning changes occur. This prevents clicks from abrupt changes.
 pub const RAMPING_RATE: f32 = 500.0;
 /// Used to reduce the wandering of brownian noise. Calculated as `x / (BROWNIAN_LEAK * timestep + 1)^2`
 pub const BROWNIAN_LEAK: f32 = 0.03;
 /// The maximum amplitude of individual brownian noise samples. Does not represent the maximum power of the noise waveform overall.
 pub const BROWNIAN_STEP: f32 = 0.5;
+/// The duty of the pulse waveform when a channel is first created.
+pub const DEFAULT_PULSE_WIDTH: f32 = 0.25;
+
+/// The modulation mode where the channel is unaffected by other channels.
+pub const MODULATION_NONE: usize = 0;
+/// The modulation mode where the channel's output is multiplied by the waveform of its source.
+pub const MODULATION_RING: usize = 1;
+/// The modulation mode where the channel's waveform restarts every time the waveform of its source does.
+pub const MODULATION_SYNC: usize = 2;
+/// The modulation mode where the channel's amplitude follows the waveform of its source, scaled to 0..1
+pub const MODULATION_AMPLITUDE: usize = 3;
 
 /// All the parameters needed in order to sample from a channel.
 pub(crate) struct ChannelState {
 	/// The progress along a repeating waveform on a scale of 0..1. Alternatively, the progress towards generating a new noise sample.
-	period: f32,
+	period: f64,
 	/// The current waveform type to use.
 	waveform: usize,
 	/// The current custom waveform data loaded. Requires the waveform field to be 7 to be generated.
-	custom_waveform: waveform::CustomWaveform,
+	custom_waveform: Vec<f32>,
+	/// How the custom waveform is sampled in between its samples.
+	interpolation: usize,
 	
 	/// The current frequency of the waveform in hertz. Affects the rate at which period is increased.
 	frequency: f32,
@@ -24,28 +38,64 @@ pub(crate) struct ChannelState {
 	amplitude: f32,
 	/// The current panning of the waveform on a scale of -1..1. Affects the amplitude of both stereo samples independently.
 	panning: f32,
+	/// The current duty of the pulse waveform on a scale of 0..1
+	pulse_width: f32,
 	
 	/// The amplitude after being dampened by ramping. This is the actual value the sample uses.
 	ramped_amplitude: f32,
 	/// The panning after being dampened by ramping. This is the actual value the sample uses.
 	ramped_panning: f32,
+	/// How amplitude and panning changes are ramped on this channel, or `None` to use the chip's ramping.
+	ramping: Option<Ramping>,
+	/// The state of the ramp towards the amplitude.
+	amplitude_ramp: Ramp,
+	/// The state of the ramp towards the panning.
+	panning_ramp: Ramp,
 	
-	/// The frequency that the channel is attempting to approach.
-	frequency_slide_target: f32,
-	/// The amplitude that the channel is attempting to approach.
-	amplitude_slide_target: f32,
-	/// The panning that the channel is attempting to approach.
-	panning_slide_target: f32,
-	
-	/// The rate at which the frequency approaches ```frequency_slide_target``` in hertz/second
-	frequency_rate: f32,
-	/// The rate at which the amplitude approaches ```amplitude_slide_target``` in units/second.
-	amplitude_rate: f32,
-	/// The rate at which the panning approaches ```panning_slide_target``` in units/second.
-	panning_rate: f32,
+	/// The slides of the frequency, amplitude and panning, indexed by the `SLIDE_*` target constants.
+	slides: [Slide; SLIDE_TARGETS],
 	
 	/// The last random value that was generated by the channel. This is what will be sampled until the period elapses.
 	noise_sample: f32,
+	
+	/// The LFOs modulating the channel, indexed by the parameter they target.
+	lfos: [Lfo; LFO_TARGETS],
+	/// The arpeggio cycling the pitch of the channel.
+	arpeggio: Arpeggio,
+	/// The operators used when the channel is in FM mode. Requires the waveform field to be 8 to be generated.
+	fm: FmVoice,
+	/// The PCM sample being played back by the channel. Requires the waveform field to be 9 to be generated.
+	pcm: PcmPlayer,
+	
+	/// The two wavetables from the chip's bank that the channel morphs between, if they've been set. Requires the waveform field to be 10 to be generated.
+	wavetables: Option<[Arc<Vec<f32>>; 2]>,
+	/// The indices of the wavetables in the chip's bank.
+	wavetable_indices: [usize; 2],
+	/// The current blend between the two wavetables on a scale of 0..1, where 0 is entirely the first table.
+	wavetable_position: f32,
+	/// The wavetable position that the channel is attempting to approach.
+	wavetable_position_target: f32,
+	/// The rate at which the wavetable position approaches ```wavetable_position_target``` in units/second.
+	wavetable_position_rate: f32,
+	
+	/// How much of the channel is fed into the chip's reverb effect on a scale of 0..1
+	reverb_send: f32,
+	/// Distorts the channel's output.
+	waveshaper: Waveshaper,
+	/// Reduces the bit depth and samplerate of the channel's output.
+	bitcrusher: Bitcrusher,
+	/// The chorus applied to the channel's stereo output.
+	chorus: Chorus,
+	/// How wide the stereo image of the channel's output is. 1 leaves it unaffected.
+	stereo_width: f32,
+	
+	/// The events that have happened on the channel since they were last taken by the chip.
+	events: Vec<ChannelEvent>,
+	
+	/// How the channel is affected by the channel it is modulated by.
+	modulation: usize,
+	/// The index of the channel that modulates this one.
+	modulation_source: usize,
 }
 
 impl ChannelState {
@@ -54,55 +104,200 @@ impl ChannelState {
 		ChannelState {
 			period: 0.0,
 			waveform: 0,
-			custom_waveform: [0.0; waveform::CUSTOM_WIDTH],
+			custom_waveform: vec![0.0; waveform::CUSTOM_WIDTH],
+			interpolation: waveform::INTERPOLATION_NEAREST,
 			
 			frequency: 440.0,
 			amplitude: 0.0,
 			panning: 0.0,
+			pulse_width: DEFAULT_PULSE_WIDTH,
 			
 			ramped_amplitude: 0.0,
 			ramped_panning: 0.0,
+			ramping: None,
+			amplitude_ramp: Ramp::new(),
+			panning_ramp: Ramp::new(),
 			
-			frequency_slide_target: 440.0,
-			amplitude_slide_target: 0.0,
-			panning_slide_target: 0.0,
-			
-			frequency_rate: 0.0,
-			amplitude_rate: 0.0,
-			panning_rate: 0.0,
+			slides: [Slide::new(440.0), Slide::new(0.0), Slide::new(0.0)],
 			
 			noise_sample: 0.0,
+			
+			lfos: [Lfo::new(); LFO_TARGETS],
+			arpeggio: Arpeggio::new(),
+			fm: FmVoice::new(),
+			pcm: PcmPlayer::new(),
+			
+			wavetables: None,
+			wavetable_indices: [0, 0],
+			wavetable_position: 0.0,
+			wavetable_position_target: 0.0,
+			wavetable_position_rate: 0.0,
+			
+			reverb_send: 1.0,
+			waveshaper: Waveshaper::new(),
+			bitcrusher: Bitcrusher::new(),
+			chorus: Chorus::new(),
+			stereo_width: 1.0,
+			
+			events: Vec::new(),
+			
+			modulation: MODULATION_NONE,
+			modulation_source: 0,
 		}
 	}
 	
 	/// Samples the channel in its current state.
+	/// `modulator` is the current output of the source channel's waveform, and is ignored unless the channel uses ring or amplitude modulation.
 	#[no_mangle]
-	pub fn sample(&self) -> (f32, f32) {
-		let sample_output = match self.waveform {
-			0 => waveform::sine(self.period),
-			1 => waveform::triangle(self.period),
-			2 => waveform::rec_sine(self.period),
-			3 => waveform::saw(self.period),
-			4 => waveform::square(self.period),
-			5 => waveform::pulse(self.period),
+	pub fn sample(&mut self, modulator: f32) -> (f32, f32) {
+		let tremolo = 1.0 - (self.lfo_shape(LFO_AMPLITUDE) * 0.5 + 0.5) * self.lfos[LFO_AMPLITUDE].amount();
+		let modulation = match self.modulation {
+			MODULATION_RING => modulator,
+			MODULATION_AMPLITUDE => modulator * 0.5 + 0.5,
+			_ => 1.0,
+		};
+		let sample_output = self.oscillator() * modulation * self.ramped_amplitude * tremolo.max(0.0);
+		let sample_output = self.bitcrusher.process(self.waveshaper.process(sample_output));
+		
+		let panning = (self.ramped_panning + self.lfo_shape(LFO_PANNING) * self.lfos[LFO_PANNING].amount()).clamp(-1.0, 1.0);
+		let left_sample = sample_output * (-panning + 1.0).min(1.0);
+		let right_sample = sample_output * (panning + 1.0).min(1.0);
+		(left_sample, right_sample)
+	}
+	
+	/// Samples the channel's waveform without applying amplitude or panning. This is what other channels are modulated by.
+	pub(crate) fn oscillator(&self) -> f32 {
+		self.oscillate(self.waveform, self.period as f32)
+	}
+	
+	/// How much of the channel is fed into the chip's reverb effect.
+	pub(crate) fn reverb_send(&self) -> f32 {
+		self.reverb_send
+	}
+	
+	/// The channel that this channel is modulated by, if any.
+	pub(crate) fn modulation_source(&self) -> Option<usize> {
+		if self.modulation == MODULATION_NONE { None }
+		else { Some(self.modulation_source) }
+	}
+	
+	/// Restarts the channel's waveform if it is hard synced to its source, and the source's waveform restarted.
+	pub(crate) fn sync(&mut self, source_wrapped: bool) {
+		if self.modulation == MODULATION_SYNC && source_wrapped {
+			self.period = 0.0;
+		}
+	}
+	
+	/// Replaces the custom waveform, which can be any length supported by `waveform::validate_custom_length`.
+	pub(crate) fn set_custom_waveform(&mut self, waveform: &[f32]) -> Result<(), LSynthError> {
+		waveform::validate_custom_length(waveform.len())?;
+		self.custom_waveform = waveform.iter().map(|value| value.clamp(-1_f32, 1_f32)).collect();
+		Ok(())
+	}
+	
+	/// Sets the two wavetables that the channel morphs between, along with their indices in the chip's bank.
+	pub(crate) fn set_wavetables(&mut self, wavetables: [Arc<Vec<f32>>; 2], indices: [usize; 2]) {
+		self.wavetables = Some(wavetables);
+		self.wavetable_indices = indices;
+	}
+	
+	/// Informs the channel that a wavetable in the chip's bank has been replaced, so that it can use the new one if it refers to it.
+	pub(crate) fn refresh_wavetable(&mut self, index: usize, wavetable: &Arc<Vec<f32>>) {
+		if let Some(wavetables) = self.wavetables.as_mut() {
+			for (current, current_index) in wavetables.iter_mut().zip(self.wavetable_indices.iter()) {
+				if *current_index == index {
+					*current = wavetable.clone();
+				}
+			}
+		}
+	}
+	
+	/// Starts playing back the provided PCM sample from the beginning.
+	pub(crate) fn set_pcm_sample(&mut self, sample: Arc<PcmSample>) {
+		self.pcm.set_sample(sample);
+	}
+	
+	/// Samples the given waveform at the given point in its period, using the channel's pulse width, noise and custom waveform where needed.
+	fn oscillate(&self, waveform: usize, period: f32) -> f32 {
+		match waveform {
+			0 => waveform::sine(period),
+			1 => waveform::triangle(period),
+			2 => waveform::rec_sine(period),
+			3 => waveform::saw(period),
+			4 => waveform::square(period),
+			5 => {
+				let modulation = self.lfo_shape(LFO_PULSE_WIDTH) * self.lfos[LFO_PULSE_WIDTH].amount();
+				waveform::pulse(period, (self.pulse_width + modulation).clamp(0.0, 1.0))
+			}
 			6 => self.noise_sample,
-			7 => waveform::custom(self.period, &self.custom_waveform),
+			7 => waveform::custom(period, &self.custom_waveform, self.interpolation),
+			8 => self.fm.sample(),
+			9 => self.pcm.sample(),
+			10 => match &self.wavetables {
+				Some([first, second]) => {
+					let first = waveform::custom(period, first, self.interpolation);
+					let second = waveform::custom(period, second, self.interpolation);
+					first + (second - first) * self.wavetable_position
+				}
+				None => 0.0,
+			},
 			_ => 0.0,
-		} * self.ramped_amplitude;
+		}
+	}
+	
+	/// Samples the waveform of an FM operator. Identical to `oscillate`, except that noise is generated fresh for every sample.
+	fn operator_oscillate(&self, shape: usize, period: f32) -> f32 {
+		match shape {
+			6 => waveform::noise(),
+			shape => self.oscillate(shape, period),
+		}
+	}
+	
+	/// Samples the waveform of the LFO for the given target, without applying its depth.
+	fn lfo_shape(&self, target: usize) -> f32 {
+		let lfo = &self.lfos[target];
+		if !lfo.is_active() { return 0.0; }
 		
-		let left_sample = sample_output * (-self.ramped_panning + 1.0).min(1.0);
-		let right_sample = sample_output * (self.ramped_panning + 1.0).min(1.0);
-		(left_sample, right_sample)
+		match lfo.shape {
+			5 => waveform::pulse(lfo.period, DEFAULT_PULSE_WIDTH),
+			6 => lfo.noise_sample,
+			shape => self.oscillate(shape, lfo.period),
+		}
 	}
 	
 	/// Updates the state of the channel by the provided timestep in seconds.
+	/// `ramping` is the chip's ramping, which is used unless the channel overrides it.
+	/// Returns whether the channel's waveform restarted during the step.
 	#[no_mangle]
-	pub fn advance(&mut self, step: f32) {
-		self.period += self.frequency * step;
+	pub fn advance(&mut self, step: f64, ramping: Ramping) -> bool {
+		let vibrato = self.lfo_shape(LFO_FREQUENCY) * self.lfos[LFO_FREQUENCY].amount();
+		let frequency = self.frequency * self.arpeggio.multiplier() * (vibrato / 12.0).exp2();
+		// The period is accumulated in double precision so that low frequencies don't lose accuracy.
+		self.period += frequency as f64 * step;
+		let wrapped = self.period >= 1.0;
+		
+		if self.waveform == 9 {
+			let was_playing = self.pcm.is_playing();
+			self.pcm.advance(frequency, step);
+			if was_playing && !self.pcm.is_playing() {
+				self.events.push(ChannelEvent::SampleEnded);
+			}
+		}
+		
+		// Everything else is precise enough in single precision.
+		let step = step as f32;
+		
+		if self.waveform == 8 {
+			let had_ended = self.fm.has_ended();
+			self.fm.advance(frequency, step);
+			if !had_ended && self.fm.has_ended() {
+				self.events.push(ChannelEvent::EnvelopeEnded);
+			}
+		}
 		
 		if self.waveform == 6 {
 			while self.period >= 1.0 {
-				let decay = self.frequency * step * BROWNIAN_LEAK + 1.0;
+				let decay = frequency * step * BROWNIAN_LEAK + 1.0;
 				self.noise_sample /= decay * decay;
 				self.noise_sample = (self.noise_sample + waveform::noise() * BROWNIAN_STEP).clamp(-1.0, 1.0);
 				self.period -= 1.0
@@ -112,11 +307,57 @@ impl ChannelState {
 		// This is a really nice way of looping ascending values around 0-1.
 		self.period -= self.period.floor();
 		
-		self.ramped_amplitude = approach(self.ramped_amplitude, self.amplitude, RAMPING_RATE * step);
-		self.ramped_panning = approach(self.ramped_panning, self.panning, RAMPING_RATE * step);
-		self.frequency = approach(self.frequency, self.frequency_slide_target, self.frequency_rate * step);
-		self.amplitude = approach(self.amplitude, self.amplitude_slide_target, self.amplitude_rate * step);
-		self.panning = approach(self.panning, self.panning_slide_target, self.panning_rate * step);
+		let ramping = self.ramping.unwrap_or(ramping);
+		self.ramped_amplitude = self.amplitude_ramp.approach(self.ramped_amplitude, self.amplitude, ramping, step);
+		self.ramped_panning = self.panning_ramp.approach(self.ramped_panning, self.panning, ramping, step);
+		for (target, slide) in self.slides.iter_mut().enumerate() {
+			let value = slide.advance(step);
+			if slide.take_finished() {
+				self.events.push(ChannelEvent::SlideFinished(target));
+			}
+			
+			match target {
+				SLIDE_FREQUENCY => self.frequency = value,
+				SLIDE_AMPLITUDE => self.amplitude = value,
+				_ => self.panning = value,
+			}
+		}
+		self.wavetable_position = approach(self.wavetable_position, self.wavetable_position_target, self.wavetable_position_rate * step);
+		
+		for lfo in self.lfos.iter_mut() {
+			lfo.advance(step);
+		}
+		self.arpeggio.advance(step);
+		self.waveshaper.advance(step);
+		self.bitcrusher.advance(step);
+		
+		if self.waveform == 8 {
+			self.refresh_fm();
+		}
+		
+		wrapped
+	}
+	
+	/// Recalculates the saved output of each FM operator from the current state of the channel.
+	fn refresh_fm(&mut self) {
+		let outputs = self.fm.calculate_outputs(|shape, period| self.operator_oscillate(shape, period));
+		self.fm.set_outputs(outputs);
+	}
+	
+	/// Applies the channel's stereo effects to a block of frames it has generated.
+	pub(crate) fn process_output(&mut self, frames: &mut [(f32, f32)], parameters: &ChipParameters) {
+		self.chorus.process(frames, parameters);
+		apply_stereo_width(frames, self.stereo_width);
+	}
+	
+	/// Takes the events that have happened on the channel since this was last called.
+	pub(crate) fn take_events(&mut self) -> Vec<ChannelEvent> {
+		std::mem::take(&mut self.events)
+	}
+	
+	/// Informs the channel that a tick of the chip has been completed.
+	pub(crate) fn tick(&mut self) {
+		self.arpeggio.tick();
 	}
 	
 	/// Executes the provided command immediately.
@@ -127,54 +368,91 @@ impl ChannelState {
 				let value = value.clamp(0_f32, 1_f32);
 				self.amplitude = value;
 				self.ramped_amplitude = value;
-				self.amplitude_slide_target = value;
+				self.amplitude_ramp.finish();
+				self.slides[SLIDE_AMPLITUDE].set(value);
 			}
 			
 			Command::SetAmplitude(value) => {
 				let value = value.clamp(0_f32, 1_f32);
 				self.amplitude = value;
-				self.amplitude_slide_target = value;
+				self.amplitude_ramp.restart(self.ramped_amplitude);
+				self.slides[SLIDE_AMPLITUDE].set(value);
 			}
 			
 			Command::AmplitudeSlide(value, rate) => {
 				let value = value.clamp(0_f32, 1_f32);
-				self.amplitude_slide_target = value;
-				self.amplitude_rate = rate;
+				self.slides[SLIDE_AMPLITUDE].start_rate(self.amplitude, value, rate);
+			}
+			
+			Command::AmplitudeSlideTime(value, time) => {
+				let value = value.clamp(0_f32, 1_f32);
+				self.slides[SLIDE_AMPLITUDE].start_time(self.amplitude, value, time / 1000.0);
 			}
 			
 			Command::SetFrequency(value) => {
 				let value = value.max(0_f32);
 				self.frequency = value;
-				self.frequency_slide_target = value;
+				self.slides[SLIDE_FREQUENCY].set(value);
+				
+				for lfo in self.lfos.iter_mut() {
+					lfo.restart_delay();
+				}
 			}
 			
 			Command::FrequencySlide(value, rate) => {
 				let value = value.max(0_f32);
-				self.frequency_slide_target = value;
-				self.frequency_rate = rate;
+				self.slides[SLIDE_FREQUENCY].start_rate(self.frequency, value, rate);
+			}
+			
+			Command::FrequencySlideTime(value, time) => {
+				let value = value.max(0_f32);
+				self.slides[SLIDE_FREQUENCY].start_time(self.frequency, value, time / 1000.0);
 			}
 			
 			Command::ForceSetPanning(value) => {
 				let value = value.clamp(-1_f32, 1_f32);
 				self.panning = value;
 				self.ramped_panning = value;
-				self.panning_slide_target = value;
+				self.panning_ramp.finish();
+				self.slides[SLIDE_PANNING].set(value);
 			}
 			
 			Command::SetPanning(value) => {
 				let value = value.clamp(-1_f32, 1_f32);
 				self.panning = value;
-				self.panning_slide_target = value;
+				self.panning_ramp.restart(self.ramped_panning);
+				self.slides[SLIDE_PANNING].set(value);
 			}
 			
 			Command::PanningSlide(value, rate) => {
 				let value = value.clamp(-1_f32, 1_f32);
-				self.panning_slide_target = value;
-				self.panning_rate = rate;
+				self.slides[SLIDE_PANNING].start_rate(self.panning, value, rate);
 			}
 			
+			Command::PanningSlideTime(value, time) => {
+				let value = value.clamp(-1_f32, 1_f32);
+				self.slides[SLIDE_PANNING].start_time(self.panning, value, time / 1000.0);
+			}
+			
+			Command::SetSlideCurve(target, curve) => {
+				if target >= SLIDE_TARGETS {
+					return Err(LSynthError::InvalidSlideTarget(InvalidSlideTargetError {
+						attempted_target: target,
+					}));
+				}
+				if curve > SLIDE_S_CURVE {
+					return Err(LSynthError::InvalidSlideCurve(InvalidSlideCurveError {
+						attempted_curve: curve,
+					}));
+				}
+				self.slides[target].set_curve(curve);
+			}
+			
+			// Converted to their time based equivalents by the chip.
+			Command::FrequencySlideTicks(_, _) | Command::AmplitudeSlideTicks(_, _) | Command::PanningSlideTicks(_, _) => {}
+			
 			Command::SetWaveform(value) => {
-				if value > 7 {
+				if value > 10 {
 					return Err(LSynthError::InvalidWaveform(InvalidWaveformError {
 						attempted_waveform: value,
 					}));
@@ -184,24 +462,252 @@ impl ChannelState {
 				}
 			}
 			
-			Command::SetCustomWaveform(mut waveform) => {
-				for value in waveform.iter_mut() {
-					*value = value.clamp(-1_f32, 1_f32);
+			Command::SetCustomWaveform(waveform) => {
+				self.set_custom_waveform(&waveform)?;
+			}
+			
+			Command::SetHarmonics(amplitudes, phases) => {
+				self.set_custom_waveform(&waveform::from_harmonics(&amplitudes, &phases, waveform::HARMONIC_WIDTH))?;
+			}
+			
+			Command::SetInterpolation(value) => {
+				if value > waveform::INTERPOLATION_CUBIC {
+					return Err(LSynthError::InvalidInterpolation(InvalidInterpolationError {
+						attempted_interpolation: value,
+					}));
 				}
-				self.custom_waveform = waveform;
+				self.interpolation = value;
 			}
 			
 			Command::SetPhase(period) => {
-				self.period = period % 1.0;
+				self.period = (period % 1.0) as f64;
+			}
+			
+			Command::SetPulseWidth(value) => {
+				self.pulse_width = value.clamp(0_f32, 1_f32);
+			}
+			
+			Command::SetLfo(target, shape, rate, depth, delay) => {
+				if target >= LFO_TARGETS {
+					return Err(LSynthError::InvalidLfoTarget(InvalidLfoTargetError {
+						attempted_target: target,
+					}));
+				}
+				if shape > 7 {
+					return Err(LSynthError::InvalidWaveform(InvalidWaveformError {
+						attempted_waveform: shape,
+					}));
+				}
+				self.lfos[target].set(shape, rate, depth, delay);
+			}
+			
+			Command::SetArpeggio(offsets, length, ticks) => {
+				self.arpeggio.set_ticks(offsets, length, ticks);
+			}
+			
+			Command::SetArpeggioRate(offsets, length, rate) => {
+				self.arpeggio.set_rate(offsets, length, rate)?;
+			}
+			
+			Command::SetFmOperator(operator, ratio, level, shape) => {
+				validate_operator(operator)?;
+				if shape > 7 {
+					return Err(LSynthError::InvalidWaveform(InvalidWaveformError {
+						attempted_waveform: shape,
+					}));
+				}
+				self.fm.set_operator(operator, ratio, level, shape);
+			}
+			
+			Command::SetFmEnvelope(operator, attack, decay, sustain, release) => {
+				validate_operator(operator)?;
+				self.fm.set_envelope(operator, attack, decay, sustain, release);
+			}
+			
+			Command::SetFmAlgorithm(algorithm) => {
+				if algorithm >= FM_ALGORITHMS {
+					return Err(LSynthError::InvalidFmAlgorithm(InvalidFmAlgorithmError {
+						attempted_algorithm: algorithm,
+					}));
+				}
+				self.fm.set_algorithm(algorithm);
+			}
+			
+			Command::SetFmFeedback(feedback) => {
+				self.fm.set_feedback(feedback);
+			}
+			
+			Command::FmKeyOn => {
+				self.fm.key_on();
+			}
+			
+			Command::FmKeyOff => {
+				self.fm.key_off();
+			}
+			
+			Command::SetModulation(mode, source) => {
+				if mode > MODULATION_AMPLITUDE {
+					return Err(LSynthError::InvalidModulation(InvalidModulationError {
+						attempted_modulation: mode,
+					}));
+				}
+				self.modulation = mode;
+				self.modulation_source = source;
+			}
+			
+			// PCM samples and wavetables are stored by the chip, which provides them with set_pcm_sample and set_wavetables instead.
+			Command::SetPcmSample(_) | Command::SetWavetables(_, _) => {}
+			
+			Command::SetBitDepth(value) => {
+				self.bitcrusher.set_bit_depth(value);
+			}
+			
+			Command::BitDepthSlide(value, rate) => {
+				self.bitcrusher.bit_depth_slide(value, rate);
+			}
+			
+			Command::SetDecimation(value) => {
+				self.bitcrusher.set_decimation(value);
+			}
+			
+			Command::DecimationSlide(value, rate) => {
+				self.bitcrusher.decimation_slide(value, rate);
+			}
+			
+			Command::SetDistortion(value) => {
+				if value > DISTORTION_FOLD {
+					return Err(LSynthError::InvalidDistortion(InvalidDistortionError {
+						attempted_distortion: value,
+					}));
+				}
+				self.waveshaper.set_mode(value);
+			}
+			
+			Command::SetDrive(value) => {
+				self.waveshaper.set_drive(value);
+			}
+			
+			Command::DriveSlide(value, rate) => {
+				self.waveshaper.drive_slide(value, rate);
+			}
+			
+			Command::SetDistortionAsymmetry(value) => {
+				self.waveshaper.set_asymmetry(value);
+			}
+			
+			Command::SetRamping(ramping) => {
+				self.ramping = Some(ramping);
+				self.amplitude_ramp.restart(self.ramped_amplitude);
+				self.panning_ramp.restart(self.ramped_panning);
+			}
+			
+			Command::ResetRamping => {
+				self.ramping = None;
+				self.amplitude_ramp.restart(self.ramped_amplitude);
+				self.panning_ramp.restart(self.ramped_panning);
+			}
+			
+			Command::SetChorus(rate, depth, mix) => {
+				self.chorus.set_rate(rate);
+				self.chorus.set_depth(depth);
+				self.chorus.set_mix(mix);
+			}
+			
+			Command::SetStereoWidth(value) => {
+				self.stereo_width = value.clamp(0_f32, 2_f32);
+			}
+			
+			Command::SetReverbSend(value) => {
+				self.reverb_send = value.clamp(0_f32, 1_f32);
+			}
+			
+			Command::SetWavetablePosition(value) => {
+				let value = value.clamp(0_f32, 1_f32);
+				self.wavetable_position = value;
+				self.wavetable_position_target = value;
+			}
+			
+			Command::WavetablePositionSlide(value, rate) => {
+				let value = value.clamp(0_f32, 1_f32);
+				self.wavetable_position_target = value;
+				self.wavetable_position_rate = rate;
 			}
 			//_ => panic!("Command not implemented"),
 		};
+		
+		// Commands can change the output of the FM voice, which is otherwise only recalculated as the channel advances.
+		if self.waveform == 8 {
+			self.refresh_fm();
+		}
 		Ok(())
 	}
 }
 
+/// Returns an error if the provided index doesn't refer to an FM operator.
+fn validate_operator(operator: usize) -> Result<(), LSynthError> {
+	if operator >= FM_OPERATORS {
+		return Err(LSynthError::InvalidFmOperator(InvalidFmOperatorError {
+			attempted_operator: operator,
+		}));
+	}
+	Ok(())
+}
+
+/// The state of a value being ramped towards its target.
+/// When ramping over a fixed time, the value moves linearly from where the ramp started to the target,
+/// and follows the target exactly once the time has passed, so that slides aren't smoothed away.
+struct Ramp {
+	/// The value the ramp started from.
+	start: f32,
+	/// How long the ramp has been running in seconds.
+	elapsed: f32,
+	/// The ramping the ramp was last advanced with, so that it can restart when the ramping changes.
+	ramping: Option<Ramping>,
+}
+
+impl Ramp {
+	/// Creates a ramp which has already reached its target.
+	fn new() -> Ramp {
+		Ramp {
+			start: 0.0,
+			elapsed: f32::INFINITY,
+			ramping: None,
+		}
+	}
+	
+	/// Starts ramping from the provided value towards the target.
+	fn restart(&mut self, value: f32) {
+		self.start = value;
+		self.elapsed = 0.0;
+	}
+	
+	/// Ends the ramp, so that the value follows the target immediately.
+	fn finish(&mut self) {
+		self.elapsed = f32::INFINITY;
+	}
+	
+	/// Advances value towards target by the provided timestep in seconds, following the provided ramping.
+	fn approach(&mut self, value: f32, target: f32, ramping: Ramping, step: f32) -> f32 {
+		// The chip's ramping can change without the channel being told, so the ramp restarts whenever it differs.
+		if self.ramping != Some(ramping) {
+			self.ramping = Some(ramping);
+			self.restart(value);
+		}
+		
+		match ramping {
+			Ramping::Rate(rate) if rate > 0.0 => approach(value, target, rate * step),
+			Ramping::Time(time) if time > 0.0 => {
+				self.elapsed += step;
+				let progress = (self.elapsed / (time / 1000.0)).min(1.0);
+				self.start + (target - self.start) * progress
+			}
+			_ => target,
+		}
+	}
+}
+
 /// Advances value towards target with the provided step.
-fn approach(value: f32, target: f32, step: f32) -> f32 {
+pub(crate) fn approach(value: f32, target: f32, step: f32) -> f32 {
 	let abs_rate = step.abs();
 	value + (target - value).min(abs_rate).max(-abs_rate)
 }
\ No newline at end of file
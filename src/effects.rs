@@ -0,0 +1,90 @@
+//! Contains the effects that can be applied to the output of individual channels, and of the chip as a whole.
+
+pub(crate) mod bitcrusher;
+pub(crate) mod chorus;
+mod compressor;
+pub(crate) mod dc_blocker;
+mod delay;
+mod equalizer;
+mod reverb;
+pub(crate) mod waveshaper;
+
+use crate::{ChipParameters, DcBlocking, MasterCommand};
+use chorus::{Chorus, apply_stereo_width};
+use compressor::Compressor;
+use dc_blocker::DcBlocker;
+use delay::Delay;
+use equalizer::Equalizer;
+use reverb::Reverb;
+
+/// The chain of effects applied to the mixed output of every channel, before it is clipped.
+pub(crate) struct MasterBus {
+	/// Removes DC offset from the mix, when enabled in the chip's parameters.
+	dc_blocker: DcBlocker,
+	/// The chorus effect.
+	chorus: Chorus,
+	/// How wide the stereo image of the mix is. 1 leaves it unaffected.
+	stereo_width: f32,
+	/// The stereo delay effect.
+	delay: Delay,
+	/// The reverb effect, which is fed by the send level of each channel.
+	reverb: Reverb,
+	/// The parametric EQ, whose bands are set in the chip's parameters.
+	equalizer: Equalizer,
+	/// The compressor, applied last so that it can catch peaks before the output is clipped.
+	compressor: Compressor,
+}
+
+impl MasterBus {
+	/// Creates a new master bus with every effect disabled.
+	pub(crate) fn new() -> MasterBus {
+		MasterBus {
+			dc_blocker: DcBlocker::new(),
+			chorus: Chorus::new(),
+			stereo_width: 1.0,
+			delay: Delay::new(),
+			reverb: Reverb::new(),
+			equalizer: Equalizer::new(),
+			compressor: Compressor::new(),
+		}
+	}
+	
+	/// Applies every effect to the provided frames in place.
+	/// `reverb_sends` is the output of every channel mixed together, scaled by each channel's reverb send level,
+	/// and `channels` is the output of each channel, which the compressor may be keyed from.
+	pub(crate) fn process(&mut self, frames: &mut [(f32, f32)], reverb_sends: &[(f32, f32)], channels: &[Vec<(f32, f32)>], parameters: &ChipParameters) {
+		if parameters.dc_blocking == DcBlocking::Master {
+			self.dc_blocker.process(frames, parameters);
+		}
+		self.chorus.process(frames, parameters);
+		apply_stereo_width(frames, self.stereo_width);
+		self.delay.process(frames, parameters);
+		self.reverb.process(frames, reverb_sends, parameters);
+		self.equalizer.process(frames, parameters);
+		self.compressor.process(frames, channels, parameters);
+	}
+	
+	/// Executes the provided command immediately.
+	pub(crate) fn execute_command(&mut self, command: MasterCommand) {
+		match command {
+			MasterCommand::SetDelayTime(time) => self.delay.set_time(time),
+			MasterCommand::SetDelayTicks(ticks) => self.delay.set_ticks(ticks),
+			MasterCommand::SetDelayFeedback(feedback) => self.delay.set_feedback(feedback),
+			MasterCommand::SetDelayPingPong(ping_pong) => self.delay.set_ping_pong(ping_pong),
+			MasterCommand::SetDelayMix(mix) => self.delay.set_mix(mix),
+			MasterCommand::SetReverbRoomSize(room_size) => self.reverb.set_room_size(room_size),
+			MasterCommand::SetReverbDamping(damping) => self.reverb.set_damping(damping),
+			MasterCommand::SetReverbMix(mix) => self.reverb.set_mix(mix),
+			MasterCommand::SetChorusRate(rate) => self.chorus.set_rate(rate),
+			MasterCommand::SetChorusDepth(depth) => self.chorus.set_depth(depth),
+			MasterCommand::SetChorusMix(mix) => self.chorus.set_mix(mix),
+			MasterCommand::SetStereoWidth(width) => self.stereo_width = width.clamp(0.0, 2.0),
+			MasterCommand::SetCompressorThreshold(threshold) => self.compressor.set_threshold(threshold),
+			MasterCommand::SetCompressorRatio(ratio) => self.compressor.set_ratio(ratio),
+			MasterCommand::SetCompressorAttack(attack) => self.compressor.set_attack(attack),
+			MasterCommand::SetCompressorRelease(release) => self.compressor.set_release(release),
+			MasterCommand::SetCompressorSidechain(channel) => self.compressor.set_sidechain(Some(channel)),
+			MasterCommand::ClearCompressorSidechain => self.compressor.set_sidechain(None),
+		}
+	}
+}
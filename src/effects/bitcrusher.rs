@@ -0,0 +1,108 @@
+//! Contains a bitcrusher which lowers the bit depth and samplerate of a channel.
+
+use crate::channel::approach;
+
+/// The highest bit depth a bitcrusher can be set to, at which it no longer affects the signal.
+pub const MAX_BIT_DEPTH: f32 = 24.0;
+
+/// Reduces the bit depth of a signal, and holds samples to simulate a lower samplerate.
+pub(crate) struct Bitcrusher {
+	/// The number of bits the signal is quantized to, including the sign bit. Fractional bit depths are allowed, so that it can be slid smoothly.
+	bit_depth: f32,
+	/// The bit depth that the bitcrusher is attempting to approach.
+	bit_depth_target: f32,
+	/// The rate at which the bit depth approaches ```bit_depth_target``` in bits/second.
+	bit_depth_rate: f32,
+	
+	/// The rate in hertz at which new samples are taken from the signal. 0 disables sample holding.
+	decimation: f32,
+	/// The decimation rate that the bitcrusher is attempting to approach.
+	decimation_target: f32,
+	/// The rate at which the decimation rate approaches ```decimation_target``` in hertz/second.
+	decimation_rate: f32,
+	
+	/// The progress towards taking a new sample on a scale of 0..1
+	hold_period: f32,
+	/// Whether a new sample should be taken the next time the signal is processed.
+	sample_due: bool,
+	/// The sample being held.
+	held_sample: f32,
+}
+
+impl Bitcrusher {
+	/// Creates a bitcrusher which doesn't affect the signal.
+	pub(crate) fn new() -> Bitcrusher {
+		Bitcrusher {
+			bit_depth: MAX_BIT_DEPTH,
+			bit_depth_target: MAX_BIT_DEPTH,
+			bit_depth_rate: 0.0,
+			
+			decimation: 0.0,
+			decimation_target: 0.0,
+			decimation_rate: 0.0,
+			
+			hold_period: 0.0,
+			sample_due: true,
+			held_sample: 0.0,
+		}
+	}
+	
+	/// Sets the bit depth immediately.
+	pub(crate) fn set_bit_depth(&mut self, bit_depth: f32) {
+		let bit_depth = bit_depth.clamp(1.0, MAX_BIT_DEPTH);
+		self.bit_depth = bit_depth;
+		self.bit_depth_target = bit_depth;
+	}
+	
+	/// Gradually changes the bit depth towards a target with the specified rate of change.
+	pub(crate) fn bit_depth_slide(&mut self, bit_depth: f32, rate: f32) {
+		self.bit_depth_target = bit_depth.clamp(1.0, MAX_BIT_DEPTH);
+		self.bit_depth_rate = rate;
+	}
+	
+	/// Sets the decimation rate immediately.
+	pub(crate) fn set_decimation(&mut self, decimation: f32) {
+		let decimation = decimation.max(0.0);
+		self.decimation = decimation;
+		self.decimation_target = decimation;
+	}
+	
+	/// Gradually changes the decimation rate towards a target with the specified rate of change.
+	pub(crate) fn decimation_slide(&mut self, decimation: f32, rate: f32) {
+		self.decimation_target = decimation.max(0.0);
+		self.decimation_rate = rate;
+	}
+	
+	/// Crushes a single sample of the signal.
+	pub(crate) fn process(&mut self, input: f32) -> f32 {
+		if self.decimation <= 0.0 {
+			return self.quantize(input);
+		}
+		
+		if self.sample_due {
+			self.held_sample = self.quantize(input);
+			self.sample_due = false;
+		}
+		self.held_sample
+	}
+	
+	/// Reduces the bit depth of a single sample.
+	fn quantize(&self, input: f32) -> f32 {
+		if self.bit_depth >= MAX_BIT_DEPTH { return input; }
+		
+		let levels = (self.bit_depth - 1.0).exp2();
+		(input * levels).round() / levels
+	}
+	
+	/// Updates the state of the bitcrusher by the provided timestep in seconds.
+	pub(crate) fn advance(&mut self, step: f32) {
+		self.bit_depth = approach(self.bit_depth, self.bit_depth_target, self.bit_depth_rate * step);
+		self.decimation = approach(self.decimation, self.decimation_target, self.decimation_rate * step);
+		
+		self.hold_period += self.decimation * step;
+		if self.hold_period >= 1.0 {
+			self.hold_period -= self.hold_period.floor();
+			self.sample_due = true;
+		}
+	}
+}
@@ -0,0 +1,104 @@
+//! Contains a chorus effect and a stereo width control.
+
+use crate::{ChipParameters, waveform};
+
+/// The delay in seconds around which the chorus oscillates.
+const BASE_DELAY: f32 = 0.015;
+/// The largest depth in seconds that the chorus can be set to.
+pub const MAX_CHORUS_DEPTH: f32 = 0.01;
+
+/// A stereo chorus, which thickens a signal by mixing it with copies of itself delayed by oscillating amounts.
+/// The delays of the left and right sides oscillate a quarter cycle apart, which also widens the signal.
+pub(crate) struct Chorus {
+	/// The rate at which the delays oscillate in hertz.
+	rate: f32,
+	/// How far the delays oscillate in seconds.
+	depth: f32,
+	/// The balance between the dry and delayed signals on a scale of 0..1, where 0 disables the chorus.
+	mix: f32,
+	/// The progress along the oscillation of the delays on a scale of 0..1
+	period: f32,
+	/// The frames that have been written into the chorus. Allocated to fit the longest delay the first time the chorus is used.
+	buffer: Vec<(f32, f32)>,
+	/// The index in the buffer that the next frame will be written to.
+	position: usize,
+}
+
+impl Chorus {
+	/// Creates a new chorus which has no effect until its mix is raised.
+	pub(crate) fn new() -> Chorus {
+		Chorus {
+			rate: 0.8,
+			depth: 0.003,
+			mix: 0.0,
+			period: 0.0,
+			buffer: Vec::new(),
+			position: 0,
+		}
+	}
+	
+	/// Sets the rate at which the delays oscillate in hertz.
+	pub(crate) fn set_rate(&mut self, rate: f32) {
+		self.rate = rate.max(0.0);
+	}
+	
+	/// Sets how far the delays oscillate in seconds.
+	pub(crate) fn set_depth(&mut self, depth: f32) {
+		self.depth = depth.clamp(0.0, MAX_CHORUS_DEPTH);
+	}
+	
+	/// Sets the balance between the dry and delayed signals.
+	pub(crate) fn set_mix(&mut self, mix: f32) {
+		self.mix = mix.clamp(0.0, 1.0);
+	}
+	
+	/// Applies the chorus to the provided frames in place.
+	pub(crate) fn process(&mut self, frames: &mut [(f32, f32)], parameters: &ChipParameters) {
+		if self.mix == 0.0 { return; }
+		
+		let samplerate = parameters.samplerate as f32;
+		let buffer_length = ((BASE_DELAY + MAX_CHORUS_DEPTH) * samplerate) as usize + 2;
+		if self.buffer.len() != buffer_length {
+			self.buffer = vec![(0.0, 0.0); buffer_length];
+			self.position = 0;
+		}
+		
+		for frame in frames.iter_mut() {
+			self.buffer[self.position] = *frame;
+			
+			let left_delay = (BASE_DELAY + self.depth * waveform::sine(self.period)) * samplerate;
+			let right_delay = (BASE_DELAY + self.depth * waveform::sine(self.period + 0.25)) * samplerate;
+			let wet_l = self.read(left_delay).0;
+			let wet_r = self.read(right_delay).1;
+			
+			frame.0 += (wet_l - frame.0) * self.mix;
+			frame.1 += (wet_r - frame.1) * self.mix;
+			
+			self.position = (self.position + 1) % buffer_length;
+			self.period = (self.period + self.rate * parameters.timestep as f32).fract();
+		}
+	}
+	
+	/// Reads a frame from the given number of frames ago, interpolating linearly between frames.
+	fn read(&self, delay: f32) -> (f32, f32) {
+		let length = self.buffer.len();
+		let whole = delay.floor() as usize;
+		let fraction = delay - whole as f32;
+		
+		let newer = self.buffer[(self.position + length - whole) % length];
+		let older = self.buffer[(self.position + length - whole - 1) % length];
+		(newer.0 + (older.0 - newer.0) * fraction, newer.1 + (older.1 - newer.1) * fraction)
+	}
+}
+
+/// Scales the difference between the left and right sides of the provided frames in place.
+/// A width of 0 makes the frames mono, 1 leaves them unaffected, and higher values exaggerate the stereo image.
+pub(crate) fn apply_stereo_width(frames: &mut [(f32, f32)], width: f32) {
+	if width == 1.0 { return; }
+	
+	for frame in frames.iter_mut() {
+		let mid = (frame.0 + frame.1) * 0.5;
+		let side = (frame.0 - frame.1) * 0.5 * width;
+		*frame = (mid + side, mid - side);
+	}
+}
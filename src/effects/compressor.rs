@@ -0,0 +1,105 @@
+//! Contains a compressor, which can be keyed from the output of a single channel.
+
+use crate::ChipParameters;
+
+/// The quietest level in decibels that the compressor measures, to avoid taking the logarithm of 0.
+const SILENCE_DB: f32 = -120.0;
+
+/// Converts a linear amplitude to decibels.
+fn to_db(amplitude: f32) -> f32 {
+	if amplitude > 0.0 { (20.0 * amplitude.log10()).max(SILENCE_DB) } else { SILENCE_DB }
+}
+
+/// Converts decibels to a linear amplitude.
+fn from_db(db: f32) -> f32 {
+	10_f32.powf(db / 20.0)
+}
+
+/// A stereo compressor, which reduces the level of its input when a key signal is louder than a threshold.
+/// The key is either the input itself, or the output of a single channel for ducking.
+pub(crate) struct Compressor {
+	/// The level in decibels above which the key is compressed.
+	threshold: f32,
+	/// How many decibels the key must rise above the threshold for the output to rise by one decibel. 1 disables the compressor.
+	ratio: f32,
+	/// The time in seconds it takes for the gain reduction to rise.
+	attack: f32,
+	/// The time in seconds it takes for the gain reduction to fall.
+	release: f32,
+	/// The channel whose output is used as the key, or `None` to use the input of the compressor.
+	sidechain: Option<usize>,
+	/// The current gain reduction in decibels.
+	reduction: f32,
+}
+
+impl Compressor {
+	/// Creates a new compressor which has no effect until its ratio is raised.
+	pub(crate) fn new() -> Compressor {
+		Compressor {
+			threshold: -12.0,
+			ratio: 1.0,
+			attack: 0.005,
+			release: 0.1,
+			sidechain: None,
+			reduction: 0.0,
+		}
+	}
+	
+	/// Sets the level in decibels above which the key is compressed.
+	pub(crate) fn set_threshold(&mut self, threshold: f32) {
+		self.threshold = threshold.min(0.0);
+	}
+	
+	/// Sets how many decibels the key must rise above the threshold for the output to rise by one decibel.
+	pub(crate) fn set_ratio(&mut self, ratio: f32) {
+		self.ratio = ratio.max(1.0);
+	}
+	
+	/// Sets the time in seconds it takes for the gain reduction to rise.
+	pub(crate) fn set_attack(&mut self, attack: f32) {
+		self.attack = attack.max(0.0);
+	}
+	
+	/// Sets the time in seconds it takes for the gain reduction to fall.
+	pub(crate) fn set_release(&mut self, release: f32) {
+		self.release = release.max(0.0);
+	}
+	
+	/// Sets the channel whose output is used as the key, or `None` to use the input of the compressor.
+	pub(crate) fn set_sidechain(&mut self, sidechain: Option<usize>) {
+		self.sidechain = sidechain;
+	}
+	
+	/// Applies the compressor to the provided frames in place.
+	/// `channels` contains the output of each channel for the same frames, which is used when the compressor is keyed from a channel.
+	pub(crate) fn process(&mut self, frames: &mut [(f32, f32)], channels: &[Vec<(f32, f32)>], parameters: &ChipParameters) {
+		if self.ratio == 1.0 && self.reduction == 0.0 { return; }
+		
+		let attack = smoothing(self.attack, parameters.timestep as f32);
+		let release = smoothing(self.release, parameters.timestep as f32);
+		let key = self.sidechain.and_then(|channel| channels.get(channel));
+		let slope = 1.0 - 1.0 / self.ratio;
+		
+		for (i, frame) in frames.iter_mut().enumerate() {
+			let (l, r) = match key {
+				Some(key) => key[i],
+				None => *frame,
+			};
+			let level = to_db(l.abs().max(r.abs()));
+			let target = (level - self.threshold).max(0.0) * slope;
+			
+			let coefficient = if target > self.reduction { attack } else { release };
+			self.reduction = target + (self.reduction - target) * coefficient;
+			if self.reduction < 1e-6 { self.reduction = 0.0; }
+			
+			let gain = from_db(-self.reduction);
+			frame.0 *= gain;
+			frame.1 *= gain;
+		}
+	}
+}
+
+/// Returns the coefficient of a one-pole smoothing filter that settles over the given time in seconds.
+fn smoothing(time: f32, timestep: f32) -> f32 {
+	if time > 0.0 { (-timestep / time).exp() } else { 0.0 }
+}
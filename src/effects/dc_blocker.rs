@@ -0,0 +1,37 @@
+//! Contains a filter which removes DC offset from a signal.
+
+use crate::ChipParameters;
+
+/// The frequency in hertz below which the DC blocker attenuates the signal.
+pub const DC_CUTOFF: f32 = 10.0;
+
+/// A one-pole highpass filter which removes DC offset from a stereo signal.
+pub(crate) struct DcBlocker {
+	/// The previous input frame.
+	previous_input: (f32, f32),
+	/// The previous output frame.
+	previous_output: (f32, f32),
+}
+
+impl DcBlocker {
+	/// Creates a new DC blocker.
+	pub(crate) fn new() -> DcBlocker {
+		DcBlocker {
+			previous_input: (0.0, 0.0),
+			previous_output: (0.0, 0.0),
+		}
+	}
+	
+	/// Removes DC offset from the provided frames in place.
+	pub(crate) fn process(&mut self, frames: &mut [(f32, f32)], parameters: &ChipParameters) {
+		let pole = 1.0 - std::f32::consts::TAU * DC_CUTOFF * parameters.timestep as f32;
+		
+		for frame in frames.iter_mut() {
+			let input = *frame;
+			frame.0 = input.0 - self.previous_input.0 + pole * self.previous_output.0;
+			frame.1 = input.1 - self.previous_input.1 + pole * self.previous_output.1;
+			self.previous_input = input;
+			self.previous_output = *frame;
+		}
+	}
+}
@@ -0,0 +1,103 @@
+//! Contains a stereo delay effect.
+
+use crate::ChipParameters;
+
+/// The longest time in seconds that the delay can be set to.
+pub const MAX_DELAY_TIME: f32 = 2.0;
+
+/// How the length of the delay is measured.
+#[derive(Clone, Copy)]
+enum DelayTime {
+	/// A fixed time in seconds.
+	Seconds(f32),
+	/// A number of average-length ticks of the chip, so that the echoes follow the tempo. The groove is ignored.
+	Ticks(f32),
+}
+
+/// A stereo delay, which repeats its input after a fixed amount of time.
+pub(crate) struct Delay {
+	/// The length of the delay.
+	time: DelayTime,
+	/// The portion of the delayed signal fed back into the delay on a scale of 0..1
+	feedback: f32,
+	/// Whether each echo alternates between the left and right sides.
+	ping_pong: bool,
+	/// The balance between the dry and delayed signals on a scale of 0..1, where 0 is entirely dry.
+	mix: f32,
+	/// The frames that have been written into the delay. Allocated to fit `MAX_DELAY_TIME` the first time the delay is used.
+	buffer: Vec<(f32, f32)>,
+	/// The index in the buffer that the next frame will be written to.
+	position: usize,
+}
+
+impl Delay {
+	/// Creates a new delay which has no effect until its mix is raised.
+	pub(crate) fn new() -> Delay {
+		Delay {
+			time: DelayTime::Seconds(0.25),
+			feedback: 0.5,
+			ping_pong: false,
+			mix: 0.0,
+			buffer: Vec::new(),
+			position: 0,
+		}
+	}
+	
+	/// Sets the length of the delay in seconds.
+	pub(crate) fn set_time(&mut self, time: f32) {
+		self.time = DelayTime::Seconds(time.clamp(0.0, MAX_DELAY_TIME));
+	}
+	
+	/// Sets the length of the delay in average-length ticks of the chip.
+	pub(crate) fn set_ticks(&mut self, ticks: f32) {
+		self.time = DelayTime::Ticks(ticks.max(0.0));
+	}
+	
+	/// Sets the portion of the delayed signal fed back into the delay.
+	pub(crate) fn set_feedback(&mut self, feedback: f32) {
+		self.feedback = feedback.clamp(0.0, 1.0);
+	}
+	
+	/// Sets whether each echo alternates between the left and right sides.
+	pub(crate) fn set_ping_pong(&mut self, ping_pong: bool) {
+		self.ping_pong = ping_pong;
+	}
+	
+	/// Sets the balance between the dry and delayed signals.
+	pub(crate) fn set_mix(&mut self, mix: f32) {
+		self.mix = mix.clamp(0.0, 1.0);
+	}
+	
+	/// Applies the delay to the provided frames in place.
+	pub(crate) fn process(&mut self, frames: &mut [(f32, f32)], parameters: &ChipParameters) {
+		if self.mix == 0.0 { return; }
+		
+		let buffer_length = (MAX_DELAY_TIME * parameters.samplerate as f32) as usize + 1;
+		if self.buffer.len() != buffer_length {
+			self.buffer = vec![(0.0, 0.0); buffer_length];
+			self.position = 0;
+		}
+		
+		let seconds = match self.time {
+			DelayTime::Seconds(seconds) => seconds,
+			DelayTime::Ticks(ticks) => ((ticks as f64 / parameters.tick_rate) as f32).min(MAX_DELAY_TIME),
+		};
+		let delay_frames = ((seconds * parameters.samplerate as f32).round() as usize).clamp(1, buffer_length - 1);
+		
+		for frame in frames.iter_mut() {
+			let (wet_l, wet_r) = self.buffer[(self.position + buffer_length - delay_frames) % buffer_length];
+			
+			self.buffer[self.position] = if self.ping_pong {
+				// The input enters on the left, and every echo swaps sides.
+				((frame.0 + frame.1) * 0.5 + wet_r * self.feedback, wet_l * self.feedback)
+			}
+			else {
+				(frame.0 + wet_l * self.feedback, frame.1 + wet_r * self.feedback)
+			};
+			self.position = (self.position + 1) % buffer_length;
+			
+			frame.0 += (wet_l - frame.0) * self.mix;
+			frame.1 += (wet_r - frame.1) * self.mix;
+		}
+	}
+}
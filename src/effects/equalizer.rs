@@ -0,0 +1,167 @@
+//! Contains the filters which make up the parametric EQ.
+
+use crate::{ChipParameters, eq::{EqBand, EqSettings}};
+
+/// The shapes of filter that a band of the EQ can have.
+#[derive(Clone, Copy, PartialEq)]
+enum BandShape {
+	/// Boosts or cuts frequencies below the band's frequency.
+	LowShelf,
+	/// Boosts or cuts frequencies around the band's frequency.
+	Peak,
+	/// Boosts or cuts frequencies above the band's frequency.
+	HighShelf,
+}
+
+/// A stereo biquad filter, using the coefficients from Robert Bristow-Johnson's Audio EQ Cookbook.
+struct Biquad {
+	/// The shape of the band the filter was built from.
+	shape: BandShape,
+	/// The feedforward coefficients, normalized so that a0 is 1.
+	b: [f32; 3],
+	/// The feedback coefficients a1 and a2, normalized so that a0 is 1.
+	a: [f32; 2],
+	/// The previous two input frames.
+	inputs: [(f32, f32); 2],
+	/// The previous two output frames.
+	outputs: [(f32, f32); 2],
+}
+
+impl Biquad {
+	/// Creates a filter with the provided shape and band at the given samplerate.
+	fn new(shape: BandShape, band: &EqBand, samplerate: f32) -> Biquad {
+		let gain = 10_f32.powf(band.gain / 40.0);
+		// Keeps the frequency below nyquist, where the filter would become unstable.
+		let frequency = band.frequency.clamp(1.0, samplerate * 0.49);
+		let omega = std::f32::consts::TAU * frequency / samplerate;
+		let (sin, cos) = omega.sin_cos();
+		let alpha = sin / (2.0 * band.q.max(0.01));
+		
+		let (b, a0, a) = match shape {
+			BandShape::Peak => (
+				[1.0 + alpha * gain, -2.0 * cos, 1.0 - alpha * gain],
+				1.0 + alpha / gain,
+				[-2.0 * cos, 1.0 - alpha / gain],
+			),
+			BandShape::LowShelf => {
+				let root = 2.0 * gain.sqrt() * alpha;
+				(
+					[
+						gain * ((gain + 1.0) - (gain - 1.0) * cos + root),
+						2.0 * gain * ((gain - 1.0) - (gain + 1.0) * cos),
+						gain * ((gain + 1.0) - (gain - 1.0) * cos - root),
+					],
+					(gain + 1.0) + (gain - 1.0) * cos + root,
+					[
+						-2.0 * ((gain - 1.0) + (gain + 1.0) * cos),
+						(gain + 1.0) + (gain - 1.0) * cos - root,
+					],
+				)
+			}
+			BandShape::HighShelf => {
+				let root = 2.0 * gain.sqrt() * alpha;
+				(
+					[
+						gain * ((gain + 1.0) + (gain - 1.0) * cos + root),
+						-2.0 * gain * ((gain - 1.0) + (gain + 1.0) * cos),
+						gain * ((gain + 1.0) + (gain - 1.0) * cos - root),
+					],
+					(gain + 1.0) - (gain - 1.0) * cos + root,
+					[
+						2.0 * ((gain - 1.0) - (gain + 1.0) * cos),
+						(gain + 1.0) - (gain - 1.0) * cos - root,
+					],
+				)
+			}
+		};
+		
+		Biquad {
+			shape,
+			b: [b[0] / a0, b[1] / a0, b[2] / a0],
+			a: [a[0] / a0, a[1] / a0],
+			inputs: [(0.0, 0.0); 2],
+			outputs: [(0.0, 0.0); 2],
+		}
+	}
+	
+	/// Carries on from the previous frames of another filter of the same shape, so that changing the coefficients doesn't click.
+	fn continue_from(&mut self, previous: &Biquad) {
+		if self.shape == previous.shape {
+			self.inputs = previous.inputs;
+			self.outputs = previous.outputs;
+		}
+	}
+	
+	/// Filters a single frame.
+	fn process(&mut self, input: (f32, f32)) -> (f32, f32) {
+		let [b0, b1, b2] = self.b;
+		let [a1, a2] = self.a;
+		let [x1, x2] = self.inputs;
+		let [y1, y2] = self.outputs;
+		
+		let output = (
+			b0 * input.0 + b1 * x1.0 + b2 * x2.0 - a1 * y1.0 - a2 * y2.0,
+			b0 * input.1 + b1 * x1.1 + b2 * x2.1 - a1 * y1.1 - a2 * y2.1,
+		);
+		
+		self.inputs = [input, x1];
+		self.outputs = [output, y1];
+		output
+	}
+}
+
+/// A parametric EQ, made up of a chain of biquad filters built from the chip's `EqSettings`.
+pub(crate) struct Equalizer {
+	/// The samplerate the filters were last built for.
+	samplerate: usize,
+	/// The filter of each band, starting with the low shelf and ending with the high shelf.
+	filters: Vec<Biquad>,
+	/// Whether every band is flat, in which case the filters are skipped.
+	flat: bool,
+}
+
+impl Equalizer {
+	/// Creates a new EQ with every band flat.
+	pub(crate) fn new() -> Equalizer {
+		Equalizer {
+			samplerate: 0,
+			filters: Vec::new(),
+			flat: true,
+		}
+	}
+	
+	/// Recalculates the filters from the provided settings.
+	/// Filters that were already running keep their previous frames, so that the change is smooth.
+	fn update(&mut self, settings: &EqSettings, samplerate: usize) {
+		let bands = std::iter::once((BandShape::LowShelf, &settings.low_shelf))
+			.chain(settings.peaks.iter().map(|band| (BandShape::Peak, band)))
+			.chain(std::iter::once((BandShape::HighShelf, &settings.high_shelf)));
+		
+		let previous = if self.flat { Vec::new() } else { std::mem::take(&mut self.filters) };
+		self.filters = bands.enumerate()
+			.map(|(i, (shape, band))| {
+				let mut filter = Biquad::new(shape, band, samplerate as f32);
+				// The high shelf is always last, even if the number of peaks has changed.
+				let matching = if shape == BandShape::HighShelf { previous.last() } else { previous.get(i) };
+				if let Some(matching) = matching {
+					filter.continue_from(matching);
+				}
+				filter
+			})
+			.collect();
+		self.flat = settings.is_flat();
+		self.samplerate = samplerate;
+	}
+	
+	/// Applies the EQ described by the chip's parameters to the provided frames in place.
+	pub(crate) fn process(&mut self, frames: &mut [(f32, f32)], parameters: &ChipParameters) {
+		if parameters.eq_changed || self.samplerate != parameters.samplerate {
+			self.update(&parameters.eq, parameters.samplerate);
+		}
+		if self.flat { return; }
+		
+		for frame in frames.iter_mut() {
+			*frame = self.filters.iter_mut().fold(*frame, |frame, filter| filter.process(frame));
+		}
+	}
+}
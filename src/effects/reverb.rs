@@ -0,0 +1,153 @@
+//! Contains a Freeverb style algorithmic reverb.
+
+use crate::ChipParameters;
+
+/// The lengths of the comb filters in frames at 44.1kHz.
+const COMB_LENGTHS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+/// The lengths of the allpass filters in frames at 44.1kHz.
+const ALLPASS_LENGTHS: [usize; 4] = [556, 441, 341, 225];
+/// How many frames longer the filters of the right side are than the left, to decorrelate them.
+const STEREO_SPREAD: usize = 23;
+/// The samplerate that the filter lengths are tuned for.
+const TUNING_SAMPLERATE: f32 = 44_100.0;
+/// The amount the input is scaled by before entering the comb filters, which would otherwise sum to a very loud signal.
+const INPUT_GAIN: f32 = 0.015;
+/// The amount each allpass filter feeds back into itself.
+const ALLPASS_FEEDBACK: f32 = 0.5;
+
+/// A feedback comb filter with a lowpass filter in its feedback path.
+struct Comb {
+	/// The frames previously written to the filter.
+	buffer: Vec<f32>,
+	/// The index in the buffer that will be read and written next.
+	position: usize,
+	/// The output of the lowpass filter in the feedback path.
+	filter_store: f32,
+}
+
+impl Comb {
+	/// Creates a new comb filter with the given length in frames.
+	fn new(length: usize) -> Comb {
+		Comb {buffer: vec![0.0; length.max(1)], position: 0, filter_store: 0.0}
+	}
+	
+	/// Filters a single sample.
+	fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+		let output = self.buffer[self.position];
+		self.filter_store = output * (1.0 - damping) + self.filter_store * damping;
+		self.buffer[self.position] = input + self.filter_store * feedback;
+		self.position = (self.position + 1) % self.buffer.len();
+		output
+	}
+}
+
+/// An allpass filter, which smears the phase of its input without colouring it.
+struct Allpass {
+	/// The frames previously written to the filter.
+	buffer: Vec<f32>,
+	/// The index in the buffer that will be read and written next.
+	position: usize,
+}
+
+impl Allpass {
+	/// Creates a new allpass filter with the given length in frames.
+	fn new(length: usize) -> Allpass {
+		Allpass {buffer: vec![0.0; length.max(1)], position: 0}
+	}
+	
+	/// Filters a single sample.
+	fn process(&mut self, input: f32) -> f32 {
+		let buffered = self.buffer[self.position];
+		self.buffer[self.position] = input + buffered * ALLPASS_FEEDBACK;
+		self.position = (self.position + 1) % self.buffer.len();
+		buffered - input
+	}
+}
+
+/// The filters making up one side of the reverb.
+struct ReverbSide {
+	/// The comb filters, which run in parallel.
+	combs: Vec<Comb>,
+	/// The allpass filters, which run in series after the combs.
+	allpasses: Vec<Allpass>,
+}
+
+impl ReverbSide {
+	/// Creates the filters for one side of the reverb, scaled to the given samplerate and lengthened by `spread` frames.
+	fn new(samplerate: usize, spread: usize) -> ReverbSide {
+		let scale = |length: usize| ((length + spread) as f32 * samplerate as f32 / TUNING_SAMPLERATE) as usize;
+		ReverbSide {
+			combs: COMB_LENGTHS.iter().map(|length| Comb::new(scale(*length))).collect(),
+			allpasses: ALLPASS_LENGTHS.iter().map(|length| Allpass::new(scale(*length))).collect(),
+		}
+	}
+	
+	/// Reverberates a single sample.
+	fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+		let mut output = self.combs.iter_mut().map(|comb| comb.process(input, feedback, damping)).sum();
+		for allpass in self.allpasses.iter_mut() {
+			output = allpass.process(output);
+		}
+		output
+	}
+}
+
+/// A stereo reverb, which simulates the reflections of a room.
+pub(crate) struct Reverb {
+	/// The size of the simulated room on a scale of 0..1, which controls how long the reverb rings out.
+	room_size: f32,
+	/// How quickly high frequencies die out on a scale of 0..1
+	damping: f32,
+	/// The level of the reverberated signal added to the output on a scale of 0..1, where 0 disables the reverb.
+	mix: f32,
+	/// The filters of each side, and the samplerate they were created for. Created the first time the reverb is used.
+	sides: Option<(usize, ReverbSide, ReverbSide)>,
+}
+
+impl Reverb {
+	/// Creates a new reverb which has no effect until its mix is raised.
+	pub(crate) fn new() -> Reverb {
+		Reverb {
+			room_size: 0.5,
+			damping: 0.5,
+			mix: 0.0,
+			sides: None,
+		}
+	}
+	
+	/// Sets the size of the simulated room.
+	pub(crate) fn set_room_size(&mut self, room_size: f32) {
+		self.room_size = room_size.clamp(0.0, 1.0);
+	}
+	
+	/// Sets how quickly high frequencies die out.
+	pub(crate) fn set_damping(&mut self, damping: f32) {
+		self.damping = damping.clamp(0.0, 1.0);
+	}
+	
+	/// Sets the level of the reverberated signal added to the output.
+	pub(crate) fn set_mix(&mut self, mix: f32) {
+		self.mix = mix.clamp(0.0, 1.0);
+	}
+	
+	/// Reverberates `sends`, which is the output of each channel scaled by its send level, and adds the result to `frames`.
+	pub(crate) fn process(&mut self, frames: &mut [(f32, f32)], sends: &[(f32, f32)], parameters: &ChipParameters) {
+		if self.mix == 0.0 { return; }
+		
+		let samplerate = parameters.samplerate;
+		if !matches!(self.sides, Some((sides_samplerate, _, _)) if sides_samplerate == samplerate) {
+			self.sides = Some((samplerate, ReverbSide::new(samplerate, 0), ReverbSide::new(samplerate, STEREO_SPREAD)));
+		}
+		
+		let feedback = self.room_size * 0.28 + 0.7;
+		let damping = self.damping * 0.4;
+		
+		if let Some((_, left, right)) = self.sides.as_mut() {
+			for (frame, send) in frames.iter_mut().zip(sends.iter()) {
+				let input = (send.0 + send.1) * INPUT_GAIN;
+				frame.0 += left.process(input, feedback, damping) * self.mix;
+				frame.1 += right.process(input, feedback, damping) * self.mix;
+			}
+		}
+	}
+}
@@ -0,0 +1,88 @@
+//! Contains a waveshaper which distorts the output of a channel.
+
+use crate::channel::approach;
+
+/// The waveshaping mode where the signal is left unaffected.
+pub const DISTORTION_NONE: usize = 0;
+/// The waveshaping mode where the signal is rounded off smoothly as it approaches -1..1
+pub const DISTORTION_SOFT: usize = 1;
+/// The waveshaping mode where the signal is cut off at -1..1
+pub const DISTORTION_HARD: usize = 2;
+/// The waveshaping mode where the signal is reflected back whenever it passes -1..1
+pub const DISTORTION_FOLD: usize = 3;
+
+/// Distorts a signal by amplifying it and passing it through a shaping function.
+pub(crate) struct Waveshaper {
+	/// The shaping function to use.
+	mode: usize,
+	/// The amount the signal is amplified by before being shaped.
+	drive: f32,
+	/// The drive that the waveshaper is attempting to approach.
+	drive_target: f32,
+	/// The rate at which the drive approaches ```drive_target``` in units/second.
+	drive_rate: f32,
+	/// An offset added to the signal before it is shaped on a scale of -1..1, so that the positive and negative halves are distorted differently.
+	asymmetry: f32,
+}
+
+impl Waveshaper {
+	/// Creates a waveshaper which doesn't affect the signal.
+	pub(crate) fn new() -> Waveshaper {
+		Waveshaper {
+			mode: DISTORTION_NONE,
+			drive: 1.0,
+			drive_target: 1.0,
+			drive_rate: 0.0,
+			asymmetry: 0.0,
+		}
+	}
+	
+	/// Sets the shaping function. The mode is expected to be valid.
+	pub(crate) fn set_mode(&mut self, mode: usize) {
+		self.mode = mode;
+	}
+	
+	/// Sets the drive immediately.
+	pub(crate) fn set_drive(&mut self, drive: f32) {
+		let drive = drive.max(0.0);
+		self.drive = drive;
+		self.drive_target = drive;
+	}
+	
+	/// Gradually changes the drive towards a target with the specified rate of change.
+	pub(crate) fn drive_slide(&mut self, drive: f32, rate: f32) {
+		self.drive_target = drive.max(0.0);
+		self.drive_rate = rate;
+	}
+	
+	/// Sets the offset added to the signal before it is shaped.
+	pub(crate) fn set_asymmetry(&mut self, asymmetry: f32) {
+		self.asymmetry = asymmetry.clamp(-1.0, 1.0);
+	}
+	
+	/// Distorts a single sample of the signal.
+	pub(crate) fn process(&self, input: f32) -> f32 {
+		if self.mode == DISTORTION_NONE { return input; }
+		
+		// The shaped offset is removed afterwards so that silence stays silent.
+		self.shape(input * self.drive + self.asymmetry) - self.shape(self.asymmetry)
+	}
+	
+	/// Passes a single sample through the shaping function.
+	fn shape(&self, input: f32) -> f32 {
+		match self.mode {
+			DISTORTION_SOFT => input.tanh(),
+			DISTORTION_HARD => input.clamp(-1.0, 1.0),
+			DISTORTION_FOLD => {
+				let folded = (input + 1.0).rem_euclid(4.0);
+				if folded < 2.0 { folded - 1.0 } else { 3.0 - folded }
+			}
+			_ => input,
+		}
+	}
+	
+	/// Updates the state of the waveshaper by the provided timestep in seconds.
+	pub(crate) fn advance(&mut self, step: f32) {
+		self.drive = approach(self.drive, self.drive_target, self.drive_rate * step);
+	}
+}
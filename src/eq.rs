@@ -0,0 +1,52 @@
+//! Contains the settings of the parametric EQ applied to the output of the chip.
+
+use serde::{Serialize, Deserialize};
+
+/// A single band of the EQ.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Serialize, Deserialize)]
+#[repr(C)]
+pub struct EqBand {
+	/// The center frequency of a peak band, or the corner frequency of a shelf, in hertz.
+	pub frequency: f32,
+	/// How far the band boosts or cuts in decibels. A gain of 0 leaves the output unaffected.
+	pub gain: f32,
+	/// How narrow a peak band is, or how steep a shelf is. 0.707 gives a shelf with no overshoot.
+	pub q: f32,
+}
+
+impl EqBand {
+	/// Creates a new band with the provided frequency, gain and Q.
+	pub fn new(frequency: f32, gain: f32, q: f32) -> EqBand {
+		EqBand { frequency, gain, q }
+	}
+}
+
+/// The bands of the parametric EQ applied to the output of the chip. By default, every band is flat.
+#[derive(Clone, PartialEq, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct EqSettings {
+	/// The shelf which boosts or cuts frequencies below its frequency.
+	pub low_shelf: EqBand,
+	/// Any number of bands which boost or cut frequencies around their frequencies.
+	pub peaks: Vec<EqBand>,
+	/// The shelf which boosts or cuts frequencies above its frequency.
+	pub high_shelf: EqBand,
+}
+
+impl EqSettings {
+	/// Returns whether every band is flat, in which case the EQ has no effect.
+	pub fn is_flat(&self) -> bool {
+		self.low_shelf.gain == 0.0 && self.high_shelf.gain == 0.0 && self.peaks.iter().all(|band| band.gain == 0.0)
+	}
+}
+
+impl Default for EqSettings {
+	fn default() -> Self {
+		EqSettings {
+			low_shelf: EqBand::new(100.0, 0.0, 0.707),
+			peaks: Vec::new(),
+			high_shelf: EqBand::new(8_000.0, 0.0, 0.707),
+		}
+	}
+}
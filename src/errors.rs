@@ -10,6 +10,42 @@ pub enum LSynthError {
 	InvalidChannel(InvalidChannelError),
 	/// Attempted to fill a buffer with an odd number of samples.
 	UnevenBufferSlice(UnevenBufferSliceError),
+	/// Attempted to fill multiple buffers of differing lengths.
+	MismatchedBufferLength(MismatchedBufferLengthError),
+	/// Attempted to render stems into a number of buffers that doesn't match the number of channels.
+	InvalidStemCount(InvalidStemCountError),
+	/// Failed to read or write a file.
+	Io(std::io::Error),
+	/// Attempted to set an LFO for a parameter that can't be modulated.
+	InvalidLfoTarget(InvalidLfoTargetError),
+	/// Attempted to configure an FM operator that does not exist.
+	InvalidFmOperator(InvalidFmOperatorError),
+	/// Attempted to set the FM algorithm to one that does not exist.
+	InvalidFmAlgorithm(InvalidFmAlgorithmError),
+	/// Attempted to set the modulation mode of a channel to one that does not exist.
+	InvalidModulation(InvalidModulationError),
+	/// Attempted to play back a PCM sample that has not been loaded.
+	InvalidPcmSample(InvalidPcmSampleError),
+	/// Attempted to set the loop points of a PCM sample outside of the sample, or in the wrong order.
+	InvalidLoopPoints(InvalidLoopPointsError),
+	/// Attempted to use a custom waveform with an unsupported number of samples.
+	InvalidWaveformLength(InvalidWaveformLengthError),
+	/// Attempted to set the interpolation mode of a channel to one that does not exist.
+	InvalidInterpolation(InvalidInterpolationError),
+	/// Attempted to use a wavetable that has not been loaded.
+	InvalidWavetable(InvalidWavetableError),
+	/// Attempted to set the distortion mode of a channel to one that does not exist.
+	InvalidDistortion(InvalidDistortionError),
+	/// Attempted to write more samples to a WAV file than it can hold.
+	WavTooLarge(WavTooLargeError),
+	/// Attempted to set the slide curve of a parameter that can't slide.
+	InvalidSlideTarget(InvalidSlideTargetError),
+	/// Attempted to use a slide curve that does not exist.
+	InvalidSlideCurve(InvalidSlideCurveError),
+	/// Attempted to set an arpeggio to step at a rate that isn't finite.
+	InvalidArpeggioRate(InvalidArpeggioRateError),
+	/// Attempted to create a PCM sample with a samplerate or base frequency that isn't positive and finite.
+	InvalidPcmPitch(InvalidPcmPitchError),
 }
 
 impl Debug for LSynthError {
@@ -18,6 +54,24 @@ impl Debug for LSynthError {
             Self::InvalidWaveform(err) => write!(f, "{:?}", err),
             Self::InvalidChannel(err) => write!(f, "{:?}", err),
             Self::UnevenBufferSlice(err) => write!(f, "{:?}", err),
+            Self::MismatchedBufferLength(err) => write!(f, "{:?}", err),
+            Self::InvalidStemCount(err) => write!(f, "{:?}", err),
+            Self::Io(err) => write!(f, "{:?}", err),
+            Self::InvalidLfoTarget(err) => write!(f, "{:?}", err),
+            Self::InvalidFmOperator(err) => write!(f, "{:?}", err),
+            Self::InvalidFmAlgorithm(err) => write!(f, "{:?}", err),
+            Self::InvalidModulation(err) => write!(f, "{:?}", err),
+            Self::InvalidPcmSample(err) => write!(f, "{:?}", err),
+            Self::InvalidLoopPoints(err) => write!(f, "{:?}", err),
+            Self::InvalidWaveformLength(err) => write!(f, "{:?}", err),
+            Self::InvalidInterpolation(err) => write!(f, "{:?}", err),
+            Self::InvalidWavetable(err) => write!(f, "{:?}", err),
+            Self::InvalidDistortion(err) => write!(f, "{:?}", err),
+            Self::WavTooLarge(err) => write!(f, "{:?}", err),
+            Self::InvalidSlideTarget(err) => write!(f, "{:?}", err),
+            Self::InvalidSlideCurve(err) => write!(f, "{:?}", err),
+            Self::InvalidArpeggioRate(err) => write!(f, "{:?}", err),
+            Self::InvalidPcmPitch(err) => write!(f, "{:?}", err),
         }
     }
 }
@@ -58,4 +112,228 @@ impl Debug for UnevenBufferSliceError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Provided slice length of {} is an odd number. Cannot generate stereo audio.", self.slice_length)
     }
-}
\ No newline at end of file
+}
+
+/// Occurs when attempting to fill multiple buffers that are required to be the same length, but aren't.
+pub struct MismatchedBufferLengthError {
+	/// The length that all of the slices were expected to be.
+	pub expected_length: usize,
+	/// The length of the offending slice.
+	pub slice_length: usize,
+}
+
+impl Debug for MismatchedBufferLengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provided slice length of {} does not match the expected length of {}.", self.slice_length, self.expected_length)
+    }
+}
+
+/// Occurs when attempting to render stems into a number of buffers that doesn't match the number of channels.
+pub struct InvalidStemCountError {
+	/// The number of stem buffers that were provided.
+	pub stem_count: usize,
+	/// How many channels the chip actually has.
+	pub channel_count: usize,
+}
+
+impl Debug for InvalidStemCountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provided {} stem buffers, but the chip has {} channels.", self.stem_count, self.channel_count)
+    }
+}
+
+/// Occurs when attempting to set an LFO for a parameter that can't be modulated.
+pub struct InvalidLfoTargetError {
+	/// The number that was attempted to be used as an LFO target index.
+	pub attempted_target: usize,
+}
+
+impl Debug for InvalidLfoTargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attempted to set LFO for invalid target: {}", self.attempted_target)
+    }
+}
+
+/// Occurs when attempting to configure an FM operator that does not exist.
+pub struct InvalidFmOperatorError {
+	/// The number that was attempted to be used as an operator index.
+	pub attempted_operator: usize,
+}
+
+impl Debug for InvalidFmOperatorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attempted to configure invalid FM operator: {}", self.attempted_operator)
+    }
+}
+
+/// Occurs when attempting to set the FM algorithm to one that does not exist.
+pub struct InvalidFmAlgorithmError {
+	/// The number that was attempted to be used as an algorithm index.
+	pub attempted_algorithm: usize,
+}
+
+impl Debug for InvalidFmAlgorithmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attempted to set invalid FM algorithm: {}", self.attempted_algorithm)
+    }
+}
+
+/// Occurs when attempting to set the modulation mode of a channel to one that does not exist.
+pub struct InvalidModulationError {
+	/// The number that was attempted to be used as a modulation mode.
+	pub attempted_modulation: usize,
+}
+
+impl Debug for InvalidModulationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attempted to set LSynth channel to invalid modulation mode: {}", self.attempted_modulation)
+    }
+}
+
+/// Occurs when attempting to play back a PCM sample that has not been loaded.
+pub struct InvalidPcmSampleError {
+	/// The index of the sample that was attempted to be played.
+	pub attempted_sample: usize,
+	/// How many samples have been loaded into the chip.
+	pub loaded_samples: usize,
+}
+
+impl Debug for InvalidPcmSampleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unable to play PCM sample {}. Chip only has {} samples loaded.", self.attempted_sample, self.loaded_samples)
+    }
+}
+
+/// Occurs when attempting to set the loop points of a PCM sample outside of the sample, or in the wrong order.
+pub struct InvalidLoopPointsError {
+	/// The requested start of the loop.
+	pub loop_start: usize,
+	/// The requested end of the loop.
+	pub loop_end: usize,
+	/// The number of samples in the PCM sample.
+	pub sample_length: usize,
+}
+
+impl Debug for InvalidLoopPointsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unable to loop from {} to {} in a PCM sample of length {}.", self.loop_start, self.loop_end, self.sample_length)
+    }
+}
+
+/// Occurs when attempting to use a custom waveform with an unsupported number of samples.
+pub struct InvalidWaveformLengthError {
+	/// The number of samples in the waveform.
+	pub attempted_length: usize,
+}
+
+impl Debug for InvalidWaveformLengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Custom waveform length of {} is not a power of two between {} and {}.", self.attempted_length, crate::waveform::MIN_CUSTOM_WIDTH, crate::waveform::MAX_CUSTOM_WIDTH)
+    }
+}
+
+/// Occurs when attempting to set the interpolation mode of a channel to one that does not exist.
+pub struct InvalidInterpolationError {
+	/// The number that was attempted to be used as an interpolation mode.
+	pub attempted_interpolation: usize,
+}
+
+impl Debug for InvalidInterpolationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attempted to set LSynth channel to invalid interpolation mode: {}", self.attempted_interpolation)
+    }
+}
+
+/// Occurs when attempting to use a wavetable that has not been loaded.
+pub struct InvalidWavetableError {
+	/// The index of the wavetable that was attempted to be used.
+	pub attempted_wavetable: usize,
+	/// How many wavetables have been loaded into the chip.
+	pub loaded_wavetables: usize,
+}
+
+impl Debug for InvalidWavetableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unable to use wavetable {}. Chip only has {} wavetables loaded.", self.attempted_wavetable, self.loaded_wavetables)
+    }
+}
+
+/// Occurs when attempting to set the distortion mode of a channel to one that does not exist.
+pub struct InvalidDistortionError {
+	/// The number that was attempted to be used as a distortion mode.
+	pub attempted_distortion: usize,
+}
+
+impl Debug for InvalidDistortionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attempted to set LSynth channel to invalid distortion mode: {}", self.attempted_distortion)
+    }
+}
+
+/// Occurs when attempting to write more samples to a WAV file than it can hold.
+pub struct WavTooLargeError {
+	/// The number of samples that were attempted to be written.
+	pub sample_count: usize,
+}
+
+impl Debug for WavTooLargeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unable to write {} samples to a WAV file. WAV files can't hold more than 4 GiB of samples.", self.sample_count)
+    }
+}
+
+/// Occurs when attempting to set the slide curve of a parameter that can't slide.
+pub struct InvalidSlideTargetError {
+	/// The number that was attempted to be used as a slide target index.
+	pub attempted_target: usize,
+}
+
+impl Debug for InvalidSlideTargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attempted to set slide curve for invalid target: {}", self.attempted_target)
+    }
+}
+
+/// Occurs when attempting to use a slide curve that does not exist.
+pub struct InvalidSlideCurveError {
+	/// The number that was attempted to be used as a slide curve.
+	pub attempted_curve: usize,
+}
+
+impl Debug for InvalidSlideCurveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attempted to use invalid slide curve: {}", self.attempted_curve)
+    }
+}
+
+/// Occurs when attempting to set an arpeggio to step at a rate that isn't finite.
+pub struct InvalidArpeggioRateError {
+	/// The rate in hertz that was attempted to be used.
+	pub attempted_rate: f32,
+}
+
+impl Debug for InvalidArpeggioRateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attempted to set arpeggio to invalid rate: {}", self.attempted_rate)
+    }
+}
+
+/// Occurs when attempting to create a PCM sample with a samplerate or base frequency that isn't positive and finite.
+pub struct InvalidPcmPitchError {
+	/// The samplerate in hertz that was attempted to be used.
+	pub samplerate: usize,
+	/// The base frequency in hertz that was attempted to be used.
+	pub base_frequency: f32,
+}
+
+impl Debug for InvalidPcmPitchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attempted to create PCM sample with invalid samplerate {} or base frequency {}", self.samplerate, self.base_frequency)
+    }
+}
+
+impl From<std::io::Error> for LSynthError {
+	fn from(err: std::io::Error) -> Self {
+		Self::Io(err)
+	}
+}
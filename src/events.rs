@@ -0,0 +1,43 @@
+//! Contains the events that the chip reports while generating audio, once they have been enabled with `ChipParameters::set_events_enabled`.
+
+use serde::{Serialize, Deserialize};
+
+/// The most events that can wait in the chip's queue. Once it is full, the oldest events are discarded, and counted by `ChipState::dropped_event_count`.
+pub const MAX_QUEUED_EVENTS: usize = 1024;
+
+/// Something that happened while the chip was generating audio.
+/// Events are queued in the order they are found, which is by channel within each call to generate, rather than strictly in time order.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize)]
+#[repr(C)]
+pub enum ChipEvent {
+	/// A slide reached its target. The parameters are the channel, and the target of the slide, as used by `Command::SetSlideCurve`.
+	SlideFinished(usize, usize),
+	/// Every envelope of the FM voice of a channel finished releasing. The parameter is the channel.
+	EnvelopeEnded(usize),
+	/// A one-shot PCM sample reached its end. The parameter is the channel.
+	SampleEnded(usize),
+	/// A tick of the chip was completed.
+	TickBoundary,
+}
+
+/// Something that happened on a single channel while it was advancing, which the chip reports as a `ChipEvent`.
+pub(crate) enum ChannelEvent {
+	/// A slide reached its target. The parameter is the target of the slide.
+	SlideFinished(usize),
+	/// Every envelope of the FM voice finished releasing.
+	EnvelopeEnded,
+	/// A one-shot PCM sample reached its end.
+	SampleEnded,
+}
+
+impl ChannelEvent {
+	/// Converts the event into a `ChipEvent` for the provided channel.
+	pub(crate) fn for_channel(self, channel: usize) -> ChipEvent {
+		match self {
+			ChannelEvent::SlideFinished(target) => ChipEvent::SlideFinished(channel, target),
+			ChannelEvent::EnvelopeEnded => ChipEvent::EnvelopeEnded(channel),
+			ChannelEvent::SampleEnded => ChipEvent::SampleEnded(channel),
+		}
+	}
+}
@@ -0,0 +1,78 @@
+//! Contains tools for rendering LSynth audio offline and saving it to disk.
+
+use std::{fs::File, io::{BufWriter, Write}, path::Path};
+use std::convert::TryFrom;
+use crate::{ChipState, errors::{LSynthError, WavTooLargeError}};
+
+/// Writes interlaced stereo samples to a 32 bit floating point WAV file.
+/// WAV files can't hold more than 4 GiB of samples, so longer recordings produce an error without writing anything.
+pub fn write_wav<P: AsRef<Path>>(path: P, samples: &[f32], samplerate: usize) -> Result<(), LSynthError> {
+	/// The number of channels in the file.
+	const CHANNELS: u16 = 2;
+	/// The number of bytes in a single sample.
+	const SAMPLE_SIZE: u16 = 4;
+	/// The WAV format tag for IEEE floating point samples.
+	const FORMAT_FLOAT: u16 = 3;
+	
+	// The RIFF header counts the 36 bytes of the format chunk as well as the samples.
+	let data_size = samples.len().checked_mul(SAMPLE_SIZE as usize)
+		.and_then(|size| u32::try_from(size).ok())
+		.filter(|size| size.checked_add(36).is_some())
+		.ok_or(LSynthError::WavTooLarge(WavTooLargeError {
+			sample_count: samples.len(),
+		}))?;
+	let mut writer = BufWriter::new(File::create(path)?);
+	
+	writer.write_all(b"RIFF")?;
+	writer.write_all(&(36 + data_size).to_le_bytes())?;
+	writer.write_all(b"WAVE")?;
+	
+	writer.write_all(b"fmt ")?;
+	writer.write_all(&16_u32.to_le_bytes())?;
+	writer.write_all(&FORMAT_FLOAT.to_le_bytes())?;
+	writer.write_all(&CHANNELS.to_le_bytes())?;
+	writer.write_all(&(samplerate as u32).to_le_bytes())?;
+	writer.write_all(&(samplerate as u32 * (CHANNELS * SAMPLE_SIZE) as u32).to_le_bytes())?;
+	writer.write_all(&(CHANNELS * SAMPLE_SIZE).to_le_bytes())?;
+	writer.write_all(&(SAMPLE_SIZE * 8).to_le_bytes())?;
+	
+	writer.write_all(b"data")?;
+	writer.write_all(&data_size.to_le_bytes())?;
+	for sample in samples {
+		writer.write_all(&sample.to_le_bytes())?;
+	}
+	
+	writer.flush()?;
+	Ok(())
+}
+
+/// Renders `frames` stereo frames from the chip, and writes the output of each channel to its own WAV file in `directory`,
+/// named `channel_0.wav`, `channel_1.wav` and so on. The mixed output of the chip is written to `master.wav`.
+/// 
+/// `on_tick` is called every time a tick is completed, and is where commands should be sent to the chip.
+/// As with `ChipState::render_with_ticks`, it is given the index of the tick that was just completed.
+pub fn export_stems<P: AsRef<Path>>(chip: &mut ChipState, directory: P, frames: usize, mut on_tick: impl FnMut(&mut ChipState, u64)) -> Result<(), LSynthError> {
+	let directory = directory.as_ref();
+	let length = frames * 2;
+	
+	let mut stems = vec![vec![0.0; length]; chip.channel_count()];
+	let mut master = vec![0.0; length];
+	let mut sample_index = 0;
+	
+	while sample_index < length {
+		let mut stem_slices: Vec<&mut [f32]> = stems.iter_mut().map(|stem| &mut stem[sample_index..]).collect();
+		let generated_data = chip.generate_stems(&mut stem_slices, Some(&mut master[sample_index..]))?;
+		sample_index += generated_data.generated;
+		
+		if generated_data.remaining_samples == 0 {
+			let tick_index = chip.tick_count() - 1;
+			on_tick(chip, tick_index);
+		}
+	}
+	
+	let samplerate = chip.parameters.get_sample_rate();
+	for (i, stem) in stems.iter().enumerate() {
+		write_wav(directory.join(format!("channel_{}.wav", i)), stem, samplerate)?;
+	}
+	write_wav(directory.join("master.wav"), &master, samplerate)
+}
@@ -0,0 +1,285 @@
+//! Contains the operators, envelopes and algorithms used by channels in FM mode.
+
+/// The number of operators in an FM voice.
+pub const FM_OPERATORS: usize = 4;
+
+/// Describes how the operators of an FM voice are connected to one another.
+struct Algorithm {
+	/// For each operator, a bitmask of the operators that modulate it. Operators are only ever modulated by operators with a higher index.
+	modulators: [u8; FM_OPERATORS],
+	/// A bitmask of the operators that are heard in the output.
+	carriers: u8,
+	/// The operator that modulates itself when feedback is applied.
+	feedback_operator: usize,
+}
+
+/// The available FM algorithms, indexed by the value used in `Command::SetFmAlgorithm`.
+const ALGORITHMS: [Algorithm; 6] = [
+	// 3 -> 2 -> 1 -> 0
+	Algorithm {modulators: [0b0010, 0b0100, 0b1000, 0], carriers: 0b0001, feedback_operator: 3},
+	// (3 + 2) -> 1 -> 0
+	Algorithm {modulators: [0b0010, 0b1100, 0, 0], carriers: 0b0001, feedback_operator: 3},
+	// (3 -> 2) + (1 -> 0)
+	Algorithm {modulators: [0b0010, 0, 0b1000, 0], carriers: 0b0101, feedback_operator: 3},
+	// 3 -> (2 + 1 + 0)
+	Algorithm {modulators: [0b1000, 0b1000, 0b1000, 0], carriers: 0b0111, feedback_operator: 3},
+	// 3 + 2 + 1 + 0
+	Algorithm {modulators: [0, 0, 0, 0], carriers: 0b1111, feedback_operator: 3},
+	// 1 -> 0, with operators 2 and 3 unused.
+	Algorithm {modulators: [0b0010, 0, 0, 0], carriers: 0b0001, feedback_operator: 1},
+];
+
+/// The number of available FM algorithms.
+pub const FM_ALGORITHMS: usize = ALGORITHMS.len();
+
+/// The stages that an envelope moves through after being keyed on.
+#[derive(Clone, Copy, PartialEq)]
+enum EnvelopeStage {
+	/// Rising towards full level.
+	Attack,
+	/// Falling from full level towards the sustain level.
+	Decay,
+	/// Holding at the sustain level until keyed off.
+	Sustain,
+	/// Falling towards silence after being keyed off.
+	Release,
+	/// Silent until keyed on.
+	Idle,
+}
+
+/// An ADSR envelope controlling the level of an operator.
+#[derive(Clone, Copy)]
+pub(crate) struct Envelope {
+	/// The time in seconds taken to rise from silence to full level.
+	attack: f32,
+	/// The time in seconds taken to fall from full level to silence. Falling to the sustain level takes a proportional amount of time.
+	decay: f32,
+	/// The level held while the envelope is keyed on, on a scale of 0..1
+	sustain: f32,
+	/// The time in seconds taken to fall from full level to silence after being keyed off.
+	release: f32,
+	/// The stage the envelope is currently in.
+	stage: EnvelopeStage,
+	/// The current output of the envelope on a scale of 0..1
+	level: f32,
+}
+
+impl Envelope {
+	/// Creates an envelope which is held at full level until keyed off.
+	fn new() -> Envelope {
+		Envelope {
+			attack: 0.0,
+			decay: 0.0,
+			sustain: 1.0,
+			release: 0.0,
+			stage: EnvelopeStage::Sustain,
+			level: 1.0,
+		}
+	}
+	
+	/// Replaces the timings of the envelope without affecting its current stage.
+	fn set(&mut self, attack: f32, decay: f32, sustain: f32, release: f32) {
+		self.attack = attack.max(0.0);
+		self.decay = decay.max(0.0);
+		self.sustain = sustain.clamp(0.0, 1.0);
+		self.release = release.max(0.0);
+	}
+	
+	/// Starts the attack stage from the envelope's current level.
+	fn key_on(&mut self) {
+		self.stage = EnvelopeStage::Attack;
+	}
+	
+	/// Starts the release stage from the envelope's current level.
+	fn key_off(&mut self) {
+		if self.stage != EnvelopeStage::Idle {
+			self.stage = EnvelopeStage::Release;
+		}
+	}
+	
+	/// Returns whether the envelope has finished releasing.
+	fn is_idle(&self) -> bool {
+		self.stage == EnvelopeStage::Idle
+	}
+	
+	/// Updates the state of the envelope by the provided timestep in seconds.
+	fn advance(&mut self, step: f32) {
+		match self.stage {
+			EnvelopeStage::Attack => {
+				self.level = if self.attack > 0.0 { self.level + step / self.attack } else { 1.0 };
+				if self.level >= 1.0 {
+					self.level = 1.0;
+					self.stage = EnvelopeStage::Decay;
+				}
+			}
+			EnvelopeStage::Decay => {
+				self.level = if self.decay > 0.0 { self.level - step / self.decay } else { self.sustain };
+				if self.level <= self.sustain {
+					self.level = self.sustain;
+					self.stage = EnvelopeStage::Sustain;
+				}
+			}
+			EnvelopeStage::Sustain => {
+				self.level = self.sustain;
+			}
+			EnvelopeStage::Release => {
+				self.level = if self.release > 0.0 { self.level - step / self.release } else { 0.0 };
+				if self.level <= 0.0 {
+					self.level = 0.0;
+					self.stage = EnvelopeStage::Idle;
+				}
+			}
+			EnvelopeStage::Idle => {}
+		}
+	}
+}
+
+/// A single oscillator within an FM voice.
+#[derive(Clone, Copy)]
+pub(crate) struct Operator {
+	/// The frequency of the operator relative to the frequency of the channel.
+	ratio: f32,
+	/// The output level of the operator. When modulating another operator, this is the modulation index in radians.
+	level: f32,
+	/// The waveform of the operator, using the same indices as `Command::SetWaveform`.
+	shape: usize,
+	/// The progress along the operator's waveform on a scale of 0..1
+	period: f32,
+	/// The envelope controlling the level of the operator.
+	envelope: Envelope,
+}
+
+impl Operator {
+	/// Creates a new operator with the given level, running at the frequency of the channel.
+	fn new(level: f32) -> Operator {
+		Operator {
+			ratio: 1.0,
+			level,
+			shape: 0,
+			period: 0.0,
+			envelope: Envelope::new(),
+		}
+	}
+}
+
+/// The state of a channel's FM voice.
+pub(crate) struct FmVoice {
+	/// The operators of the voice.
+	operators: [Operator; FM_OPERATORS],
+	/// The index of the algorithm connecting the operators.
+	algorithm: usize,
+	/// How strongly the feedback operator modulates itself, in radians.
+	feedback: f32,
+	/// The previous output of the feedback operator.
+	feedback_sample: f32,
+	/// The output of each operator in the voice's current state, saved so that they are only calculated once per sample.
+	outputs: [f32; FM_OPERATORS],
+}
+
+impl FmVoice {
+	/// Creates a new voice where only the first operator is audible, producing a plain sinewave.
+	pub(crate) fn new() -> FmVoice {
+		FmVoice {
+			operators: [Operator::new(1.0), Operator::new(0.0), Operator::new(0.0), Operator::new(0.0)],
+			algorithm: 0,
+			feedback: 0.0,
+			feedback_sample: 0.0,
+			outputs: [0.0; FM_OPERATORS],
+		}
+	}
+	
+	/// Sets the frequency ratio, level and shape of an operator.
+	pub(crate) fn set_operator(&mut self, operator: usize, ratio: f32, level: f32, shape: usize) {
+		let operator = &mut self.operators[operator];
+		operator.ratio = ratio.max(0.0);
+		operator.level = level;
+		operator.shape = shape;
+	}
+	
+	/// Sets the envelope timings of an operator.
+	pub(crate) fn set_envelope(&mut self, operator: usize, attack: f32, decay: f32, sustain: f32, release: f32) {
+		self.operators[operator].envelope.set(attack, decay, sustain, release);
+	}
+	
+	/// Sets the algorithm connecting the operators.
+	pub(crate) fn set_algorithm(&mut self, algorithm: usize) {
+		self.algorithm = algorithm;
+	}
+	
+	/// Sets how strongly the feedback operator modulates itself.
+	pub(crate) fn set_feedback(&mut self, feedback: f32) {
+		self.feedback = feedback;
+	}
+	
+	/// Starts the attack stage of every operator's envelope.
+	pub(crate) fn key_on(&mut self) {
+		for operator in self.operators.iter_mut() {
+			operator.envelope.key_on();
+		}
+	}
+	
+	/// Starts the release stage of every operator's envelope.
+	pub(crate) fn key_off(&mut self) {
+		for operator in self.operators.iter_mut() {
+			operator.envelope.key_off();
+		}
+	}
+	
+	/// Calculates the output of every operator. `oscillate` samples a waveform by its index at a point in its period.
+	pub(crate) fn calculate_outputs(&self, oscillate: impl Fn(usize, f32) -> f32) -> [f32; FM_OPERATORS] {
+		let algorithm = &ALGORITHMS[self.algorithm];
+		let mut outputs = [0.0; FM_OPERATORS];
+		
+		// Modulators always have a higher index than the operators they modulate, so they have already been calculated.
+		for i in (0..FM_OPERATORS).rev() {
+			let operator = &self.operators[i];
+			
+			let mut modulation: f32 = (0..FM_OPERATORS)
+				.filter(|j| algorithm.modulators[i] & (1 << j) != 0)
+				.map(|j| outputs[j])
+				.sum();
+			if i == algorithm.feedback_operator {
+				modulation += self.feedback_sample * self.feedback;
+			}
+			
+			let period = (operator.period + modulation / std::f32::consts::TAU).rem_euclid(1.0);
+			outputs[i] = oscillate(operator.shape, period) * operator.level * operator.envelope.level;
+		}
+		
+		outputs
+	}
+	
+	/// Saves the output of each operator, as calculated by `calculate_outputs`. Must be called whenever the state of the voice changes.
+	pub(crate) fn set_outputs(&mut self, outputs: [f32; FM_OPERATORS]) {
+		self.outputs = outputs;
+	}
+	
+	/// Samples the voice in its current state, using the saved output of each operator.
+	pub(crate) fn sample(&self) -> f32 {
+		let carriers = ALGORITHMS[self.algorithm].carriers;
+		
+		let output: f32 = (0..FM_OPERATORS)
+			.filter(|i| carriers & (1 << i) != 0)
+			.map(|i| self.outputs[i])
+			.sum();
+		output / carriers.count_ones() as f32
+	}
+	
+	/// Returns whether the envelope of every operator has finished releasing.
+	pub(crate) fn has_ended(&self) -> bool {
+		self.operators.iter().all(|operator| operator.envelope.is_idle())
+	}
+	
+	/// Updates the state of the voice by the provided timestep in seconds, given the frequency of the channel in hertz.
+	/// The saved outputs need to be recalculated afterwards.
+	pub(crate) fn advance(&mut self, frequency: f32, step: f32) {
+		// The same output that was sampled is fed back, so noise operators feed back exactly what they produced.
+		self.feedback_sample = self.outputs[ALGORITHMS[self.algorithm].feedback_operator];
+		
+		for operator in self.operators.iter_mut() {
+			operator.period += frequency * operator.ratio * step;
+			operator.period -= operator.period.floor();
+			operator.envelope.advance(step);
+		}
+	}
+}
@@ -0,0 +1,63 @@
+//! Contains the sample formats that LSynth is able to write generated audio in.
+
+/// A type that generated audio can be written as. LSynth generates samples as `f32` on a scale of -1..1, which are then converted to this format.
+pub trait OutputSample: Copy + Send {
+	/// The size of a single quantization step of this format on a scale of -1..1. Floating point formats use 0, and are never dithered.
+	const QUANTIZATION_STEP: f32;
+
+	/// Converts a sample on a scale of -1..1 to this format.
+	fn from_f32(value: f32) -> Self;
+}
+
+impl OutputSample for f32 {
+	const QUANTIZATION_STEP: f32 = 0.0;
+
+	fn from_f32(value: f32) -> Self {
+		value
+	}
+}
+
+impl OutputSample for f64 {
+	const QUANTIZATION_STEP: f32 = 0.0;
+
+	fn from_f32(value: f32) -> Self {
+		value as f64
+	}
+}
+
+impl OutputSample for i16 {
+	const QUANTIZATION_STEP: f32 = 1.0 / i16::MAX as f32;
+
+	fn from_f32(value: f32) -> Self {
+		(value.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+	}
+}
+
+impl OutputSample for i32 {
+	const QUANTIZATION_STEP: f32 = 1.0 / i32::MAX as f32;
+
+	fn from_f32(value: f32) -> Self {
+		// f32 can't represent i32::MAX exactly, so this has to be done in double precision.
+		(value.clamp(-1.0, 1.0) as f64 * i32::MAX as f64).round() as i32
+	}
+}
+
+impl OutputSample for u8 {
+	const QUANTIZATION_STEP: f32 = 1.0 / 127.0;
+
+	fn from_f32(value: f32) -> Self {
+		(value.clamp(-1.0, 1.0) * 127.0 + 128.0).round() as u8
+	}
+}
+
+/// Converts a sample on a scale of -1..1 to the given format.
+/// If `dither` is true and the format is quantized, triangular (TPDF) noise with a width of one quantization step is added beforehand.
+pub(crate) fn convert<T: OutputSample>(value: f32, dither: bool) -> T {
+	if dither && T::QUANTIZATION_STEP > 0.0 {
+		let noise = rand::random::<f32>() - rand::random::<f32>();
+		T::from_f32(value + noise * T::QUANTIZATION_STEP)
+	}
+	else {
+		T::from_f32(value)
+	}
+}
@@ -0,0 +1,91 @@
+//! Contains the low frequency oscillators that channels use to modulate their parameters.
+
+use crate::waveform;
+
+/// The number of parameters of a channel that can be modulated by an LFO.
+pub const LFO_TARGETS: usize = 4;
+/// The LFO target for frequency. Depth is measured in semitones.
+pub(crate) const LFO_FREQUENCY: usize = 0;
+/// The LFO target for amplitude. Depth is the portion of the amplitude removed at the LFO's lowest point.
+pub(crate) const LFO_AMPLITUDE: usize = 1;
+/// The LFO target for panning. Depth is measured in the same units as panning.
+pub(crate) const LFO_PANNING: usize = 2;
+/// The LFO target for pulse width. Depth is measured in the same units as pulse width.
+pub(crate) const LFO_PULSE_WIDTH: usize = 3;
+
+/// The state of a single low frequency oscillator.
+#[derive(Clone, Copy)]
+pub(crate) struct Lfo {
+	/// The waveform the LFO uses, using the same indices as `Command::SetWaveform`.
+	pub(crate) shape: usize,
+	/// The rate at which the LFO oscillates in hertz.
+	rate: f32,
+	/// How strongly the LFO modulates its target. The units depend on the target.
+	depth: f32,
+	/// The time in seconds that the LFO takes to fade in after being set, or after the channel's frequency is set.
+	delay: f32,
+	/// The progress along the LFO's waveform on a scale of 0..1
+	pub(crate) period: f32,
+	/// The time in seconds since the LFO started fading in.
+	elapsed: f32,
+	/// The last random value generated by the LFO. Used when the shape is noise, and held until the period elapses.
+	pub(crate) noise_sample: f32,
+}
+
+impl Lfo {
+	/// Creates a new LFO with no depth.
+	pub(crate) fn new() -> Lfo {
+		Lfo {
+			shape: 0,
+			rate: 0.0,
+			depth: 0.0,
+			delay: 0.0,
+			period: 0.0,
+			elapsed: 0.0,
+			noise_sample: 0.0,
+		}
+	}
+	
+	/// Replaces the settings of the LFO, restarting its waveform and delay.
+	pub(crate) fn set(&mut self, shape: usize, rate: f32, depth: f32, delay: f32) {
+		self.shape = shape;
+		self.rate = rate.max(0.0);
+		self.depth = depth;
+		self.delay = delay.max(0.0);
+		self.period = 0.0;
+		self.elapsed = 0.0;
+	}
+	
+	/// Starts fading the LFO in again from nothing.
+	pub(crate) fn restart_delay(&mut self) {
+		self.elapsed = 0.0;
+	}
+	
+	/// Whether the LFO currently has any effect.
+	pub(crate) fn is_active(&self) -> bool {
+		self.depth != 0.0
+	}
+	
+	/// The depth of the LFO, scaled down while it is fading in.
+	pub(crate) fn amount(&self) -> f32 {
+		if self.elapsed < self.delay {
+			self.depth * self.elapsed / self.delay
+		}
+		else {
+			self.depth
+		}
+	}
+	
+	/// Updates the state of the LFO by the provided timestep in seconds.
+	pub(crate) fn advance(&mut self, step: f32) {
+		if !self.is_active() { return; }
+		
+		self.period += self.rate * step;
+		if self.period >= 1.0 {
+			self.noise_sample = waveform::noise();
+			self.period -= self.period.floor();
+		}
+		
+		self.elapsed = (self.elapsed + step).min(self.delay);
+	}
+}
@@ -14,7 +14,7 @@
 //! let mut frequency = 110.0;
 //! let mut beat = 0;
 //! 
-//! let mut request_callback = move |chip: &mut ChipState| {
+//! let mut tick_callback = move |chip: &mut ChipState, _tick_index: u64| {
 //!     beat += 1;
 //!     while beat >= 4 {
 //!         frequency += 110.0;
@@ -25,16 +25,7 @@
 //! };
 //! 
 //! let mut audio_sample_request = move |buffer: &mut [f32]| {
-//!     let mut sample_index = 0;
-//!     
-//!     while sample_index < buffer.len() {
-//!         let generated_data = chip.generate(&mut buffer[sample_index..]).unwrap();
-//!         sample_index += generated_data.generated;
-//!         
-//!         assert!(generated_data.generated != 0);
-//!     
-//!         if generated_data.remaining_samples == 0 { request_callback(&mut chip); }
-//!     }
+//!     chip.render_with_ticks(buffer, &mut tick_callback).unwrap();
 //! };
 //! #
 //! # let mut audio_stream = [0.0; 512];
@@ -42,12 +33,28 @@
 //! ```
 
 pub mod waveform;
+pub mod arpeggio;
 mod channel;
+mod lfo;
+pub mod slide;
+mod fm;
 pub mod errors;
 pub mod c_compatible;
+pub mod format;
+pub mod export;
+pub mod pcm;
+pub mod eq;
+pub mod events;
+mod effects;
 
 use channel::ChannelState;
-use errors::{InvalidChannelError, LSynthError, UnevenBufferSliceError};
+use effects::{MasterBus, dc_blocker::DcBlocker};
+use errors::{InvalidChannelError, InvalidPcmSampleError, InvalidStemCountError, InvalidWavetableError, LSynthError, MismatchedBufferLengthError, UnevenBufferSliceError};
+use eq::EqSettings;
+use events::{ChipEvent, MAX_QUEUED_EVENTS};
+use format::OutputSample;
+use pcm::PcmSample;
+use std::{collections::VecDeque, sync::Arc};
 use serde::{Serialize, Deserialize};
 
 /// The different types of commands that can be sent to channels.
@@ -67,6 +74,9 @@ pub enum Command {
 	/// | 5 | Pulse          |
 	/// | 6 | Noise          |
 	/// | 7 | Custom         |
+	/// | 8 | FM             |
+	/// | 9 | PCM sample     |
+	/// | 10 | Wavetable     |
 	SetWaveform(usize),
 	/// An instruction to set the frequency of the channel in hertz.
 	SetFrequency(f32),
@@ -75,6 +85,8 @@ pub enum Command {
 	/// An instruction to set the panning of the channel on a scale of -1..1
 	SetPanning(f32),
 	/// An instruction to change the custom waveform stored in the channel.
+	/// Commands are fixed in size, so this always sets a waveform of `waveform::CUSTOM_WIDTH` samples.
+	/// Custom waveforms of other lengths are set with `ChipState::set_custom_waveform` instead, which is applied immediately rather than being queued like a command.
 	SetCustomWaveform(waveform::CustomWaveform),
 	/// An instruction to set the phase of a waveform directly.
 	SetPhase(f32),
@@ -90,6 +102,211 @@ pub enum Command {
 	AmplitudeSlide(f32, f32),
 	/// An instruction to gradually change the panning of the channel from its current state to a target state with the specified rate of change.
 	PanningSlide(f32, f32),
+	/// An instruction to gradually change the frequency of the channel from its current state to a target state, reaching it after the specified time in milliseconds.
+	FrequencySlideTime(f32, f32),
+	/// An instruction to gradually change the amplitude of the channel from its current state to a target state, reaching it after the specified time in milliseconds.
+	AmplitudeSlideTime(f32, f32),
+	/// An instruction to gradually change the panning of the channel from its current state to a target state, reaching it after the specified time in milliseconds.
+	PanningSlideTime(f32, f32),
+	/// An instruction to gradually change the frequency of the channel from its current state to a target state, reaching it after the specified number of ticks.
+	/// The ticks are counted from the moment the command is sent, and follow the chip's groove.
+	FrequencySlideTicks(f32, f32),
+	/// An instruction to gradually change the amplitude of the channel from its current state to a target state, reaching it after the specified number of ticks.
+	/// The ticks are counted from the moment the command is sent, and follow the chip's groove.
+	AmplitudeSlideTicks(f32, f32),
+	/// An instruction to gradually change the panning of the channel from its current state to a target state, reaching it after the specified number of ticks.
+	/// The ticks are counted from the moment the command is sent, and follow the chip's groove.
+	PanningSlideTicks(f32, f32),
+	/// An instruction to set the shape of a parameter's slides. The parameters are the target and the curve.
+	/// 
+	/// | Target | Parameter |
+	/// |--------|-----------|
+	/// | 0      | Frequency |
+	/// | 1      | Amplitude |
+	/// | 2      | Panning   |
+	/// 
+	/// | Curve | Shape       |
+	/// |-------|-------------|
+	/// | 0     | Linear      |
+	/// | 1     | Exponential |
+	/// | 2     | Logarithmic |
+	/// | 3     | S-curve     |
+	SetSlideCurve(usize, usize),
+	
+	/// An instruction to build the channel's custom waveform by adding together sinewaves at each harmonic of the fundamental.
+	/// The parameters are the amplitudes and phases of each harmonic, as described in `waveform::from_harmonics`.
+	SetHarmonics(waveform::Harmonics, waveform::Harmonics),
+	/// An instruction to set how the custom waveform is sampled in between its samples.
+	///
+	/// | Index | Interpolation |
+	/// |---|---------|
+	/// | 0 | Nearest |
+	/// | 1 | Linear  |
+	/// | 2 | Cubic   |
+	SetInterpolation(usize),
+	/// An instruction to set the duty of the pulse waveform on a scale of 0..1
+	SetPulseWidth(f32),
+	/// An instruction to set one of the channel's low frequency oscillators.
+	/// The parameters are the target, the shape, the rate in hertz, the depth, and the delay in seconds.
+	/// 
+	/// The shape uses the same indices as `SetWaveform`.
+	/// The delay is the time taken for the LFO to fade in, and restarts whenever the frequency of the channel is set.
+	/// A depth of 0 disables the LFO.
+	///
+	/// | Index | Target      | Depth units                                      |
+	/// |---|-------------|--------------------------------------------------|
+	/// | 0 | Frequency   | Semitones                                        |
+	/// | 1 | Amplitude   | Portion of the amplitude removed at the lowest point |
+	/// | 2 | Panning     | Panning units                                    |
+	/// | 3 | Pulse width | Pulse width units                                |
+	SetLfo(usize, usize, f32, f32, f32),
+	/// An instruction to cycle the pitch of the channel through a list of offsets in semitones, moving to the next offset every few ticks.
+	/// The parameters are the offsets, how many of the offsets to use, and the number of ticks per step.
+	/// 
+	/// Using no offsets stops the arpeggio.
+	SetArpeggio(arpeggio::ArpeggioOffsets, usize, usize),
+	/// Identical to `SetArpeggio`, but moves to the next offset at a rate in hertz instead of with the ticks of the chip.
+	SetArpeggioRate(arpeggio::ArpeggioOffsets, usize, f32),
+	
+	/// An instruction to configure one of the four operators used by the FM waveform.
+	/// The parameters are the operator, its frequency relative to the channel, its level, and its shape.
+	/// 
+	/// The level of a modulating operator is its modulation index in radians. The shape uses the same indices as `SetWaveform`, excluding FM.
+	SetFmOperator(usize, f32, f32, usize),
+	/// An instruction to set the envelope of an FM operator.
+	/// The parameters are the operator, the attack time, the decay time, the sustain level, and the release time. Times are in seconds.
+	SetFmEnvelope(usize, f32, f32, f32, f32),
+	/// An instruction to set how the FM operators modulate one another. Operators are numbered 0..3, and arrows point from modulators to the operators they modulate.
+	/// 
+	/// | Index | Algorithm                  | Feedback operator |
+	/// |---|----------------------------|---|
+	/// | 0 | 3 → 2 → 1 → 0              | 3 |
+	/// | 1 | (3 + 2) → 1 → 0            | 3 |
+	/// | 2 | (3 → 2) + (1 → 0)          | 3 |
+	/// | 3 | 3 → (2 + 1 + 0)            | 3 |
+	/// | 4 | 3 + 2 + 1 + 0              | 3 |
+	/// | 5 | 1 → 0                      | 1 |
+	SetFmAlgorithm(usize),
+	/// An instruction to set how strongly the feedback operator modulates itself, in radians.
+	SetFmFeedback(f32),
+	/// An instruction to start the attack of every FM operator's envelope.
+	FmKeyOn,
+	/// An instruction to start the release of every FM operator's envelope.
+	FmKeyOff,
+	
+	/// An instruction to have the channel be modulated by another channel.
+	/// The parameters are the modulation mode, and the index of the channel to be modulated by.
+	/// 
+	/// The source's waveform is used regardless of its amplitude, so a silent channel can still act as a modulator.
+	/// 
+	/// | Index | Mode                                                                  |
+	/// |---|-----------------------------------------------------------------------|
+	/// | 0 | None                                                                  |
+	/// | 1 | Ring modulation. The output is multiplied by the source's waveform.   |
+	/// | 2 | Hard sync. The waveform restarts whenever the source's waveform does. |
+	/// | 3 | Amplitude modulation. The output is multiplied by the source's waveform, scaled to 0..1 |
+	SetModulation(usize, usize),
+	
+	/// An instruction to start playing back one of the PCM samples loaded into the chip from the beginning, by the index returned when it was loaded.
+	/// Playing back a sample at the frequency it was loaded with plays it at its original pitch.
+	SetPcmSample(usize),
+	
+	/// An instruction to set the two wavetables from the chip's bank that the channel morphs between, by the indices returned when they were loaded.
+	SetWavetables(usize, usize),
+	/// An instruction to set the blend between the channel's two wavetables on a scale of 0..1, where 0 is entirely the first table.
+	SetWavetablePosition(f32),
+	/// An instruction to gradually change the blend between the channel's two wavetables from its current state to a target state with the specified rate of change.
+	WavetablePositionSlide(f32, f32),
+	
+	/// An instruction to set how much of the channel is fed into the chip's reverb effect on a scale of 0..1
+	SetReverbSend(f32),
+	
+	/// An instruction to set the number of bits the output of the channel is quantized to, between 1 and 24. 24 bits leaves the output unaffected.
+	SetBitDepth(f32),
+	/// An instruction to gradually change the bit depth of the channel from its current state to a target state with the specified rate of change.
+	BitDepthSlide(f32, f32),
+	/// An instruction to hold the output of the channel so that it only changes at the given rate in hertz, simulating a lower samplerate. 0 disables sample holding.
+	SetDecimation(f32),
+	/// An instruction to gradually change the decimation rate of the channel from its current state to a target state with the specified rate of change.
+	DecimationSlide(f32, f32),
+	
+	/// An instruction to set how the output of the channel is distorted. The output is amplified by the drive before being distorted.
+	///
+	/// | Index | Distortion                                          |
+	/// |---|-----------------------------------------------------|
+	/// | 0 | None                                                |
+	/// | 1 | Soft clipping                                       |
+	/// | 2 | Hard clipping                                       |
+	/// | 3 | Wavefolding, which reflects the output back past -1..1 |
+	SetDistortion(usize),
+	/// An instruction to set the amount the output of the channel is amplified by before being distorted.
+	SetDrive(f32),
+	/// An instruction to gradually change the drive of the channel from its current state to a target state with the specified rate of change.
+	DriveSlide(f32, f32),
+	/// An instruction to set an offset on a scale of -1..1 that is added to the output of the channel before being distorted,
+	/// so that the positive and negative halves are distorted differently.
+	SetDistortionAsymmetry(f32),
+	
+	/// An instruction to override how quickly the channel's amplitude and panning follow changes.
+	SetRamping(Ramping),
+	/// An instruction to make the channel's amplitude and panning follow changes as quickly as set in the chip's parameters again.
+	ResetRamping,
+	
+	/// An instruction to set the chorus effect applied to the channel.
+	/// The parameters are the rate in hertz, the depth in seconds, and the mix, as described in the equivalent `MasterCommand` variants.
+	SetChorus(f32, f32, f32),
+	/// An instruction to set how wide the stereo image of the channel is on a scale of 0..2, as described in `MasterCommand::SetStereoWidth`.
+	SetStereoWidth(f32),
+}
+
+/// The different types of commands that can be sent to the effects applied to the output of the whole chip.
+#[derive(Clone)]
+#[derive(Serialize, Deserialize)]
+#[repr(C)]
+pub enum MasterCommand {
+	/// An instruction to set the length of the delay effect in seconds, up to a maximum of 2 seconds.
+	SetDelayTime(f32),
+	/// An instruction to set the length of the delay effect in ticks, so that it follows the tick rate of the chip.
+	/// The ticks are of average length, ignoring the groove, so that every echo is the same length.
+	SetDelayTicks(f32),
+	/// An instruction to set the portion of the delayed signal fed back into the delay effect on a scale of 0..1
+	SetDelayFeedback(f32),
+	/// An instruction to set whether each echo of the delay effect alternates between the left and right sides.
+	SetDelayPingPong(bool),
+	/// An instruction to set the balance between the dry and delayed signals on a scale of 0..1, where 0 disables the delay effect.
+	SetDelayMix(f32),
+	
+	/// An instruction to set the size of the room simulated by the reverb effect on a scale of 0..1, which controls how long it rings out.
+	SetReverbRoomSize(f32),
+	/// An instruction to set how quickly high frequencies die out in the reverb effect on a scale of 0..1
+	SetReverbDamping(f32),
+	/// An instruction to set the level of the reverberated signal added to the output on a scale of 0..1, where 0 disables the reverb effect.
+	/// How much of each channel is reverberated is set with `Command::SetReverbSend`.
+	SetReverbMix(f32),
+	
+	/// An instruction to set the rate at which the delays of the chorus effect oscillate in hertz.
+	SetChorusRate(f32),
+	/// An instruction to set how far the delays of the chorus effect oscillate in seconds, up to a maximum of 0.01 seconds.
+	SetChorusDepth(f32),
+	/// An instruction to set the balance between the dry and delayed signals of the chorus effect on a scale of 0..1, where 0 disables the chorus effect.
+	SetChorusMix(f32),
+	/// An instruction to set how wide the stereo image of the chip's output is on a scale of 0..2
+	/// 0 is mono, 1 leaves the output unaffected, and higher values exaggerate the difference between the left and right sides.
+	SetStereoWidth(f32),
+	
+	/// An instruction to set the level in decibels above which the compressor reduces the level of the output.
+	SetCompressorThreshold(f32),
+	/// An instruction to set how many decibels the key of the compressor must rise above the threshold for the output to rise by one decibel.
+	/// A ratio of 1 disables the compressor.
+	SetCompressorRatio(f32),
+	/// An instruction to set the time in seconds it takes for the compressor to respond to the key rising above the threshold.
+	SetCompressorAttack(f32),
+	/// An instruction to set the time in seconds it takes for the compressor to recover once the key falls below the threshold.
+	SetCompressorRelease(f32),
+	/// An instruction to key the compressor from the output of the given channel instead of the mix, so that the channel ducks the rest of the chip.
+	SetCompressorSidechain(usize),
+	/// An instruction to key the compressor from the mix again.
+	ClearCompressorSidechain,
 }
 
 /// The current state of the LSynth chip.
@@ -98,8 +315,64 @@ pub struct ChipState {
 	channels: Vec<ChannelState>,
 	/// Details how this chip is intended to operate.
 	pub parameters: ChipParameters,
-	/// How many frames are left in this tick.
-	remaining_frames: f32,
+	/// How many frames the chip has generated since it was created.
+	frames_rendered: u64,
+	/// The point that tick boundaries are measured from. Boundaries are calculated from this rather than accumulated, so they never drift.
+	tick_anchor: TickAnchor,
+	/// The PCM samples that have been loaded into the chip, which channels can play back.
+	pcm_samples: Vec<Arc<PcmSample>>,
+	/// The bank of wavetables that have been loaded into the chip, which channels can morph between.
+	wavetables: Vec<Arc<Vec<f32>>>,
+	/// The effects applied to the mixed output of the chip.
+	master_bus: MasterBus,
+	/// The DC blockers for the output of each channel, used when DC blocking is set to `DcBlocking::Channels`.
+	channel_dc_blockers: Vec<DcBlocker>,
+	/// The events reported while generating audio, waiting to be taken.
+	events: VecDeque<ChipEvent>,
+	/// How many events have been discarded because the queue was full.
+	dropped_events: u64,
+	/// How many ticks the chip has completed.
+	ticks: u64,
+	/// The audio generated by the last render, reused between renders.
+	buffers: RenderBuffers,
+}
+
+/// Where DC offset is removed from the output of the chip.
+/// The rectified sine, pulse and noise waveforms are not centered around 0, which wastes headroom and causes thumps as channels start and stop.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Serialize, Deserialize)]
+#[repr(C)]
+pub enum DcBlocking {
+	/// DC offset is not removed.
+	#[default]
+	Off,
+	/// DC offset is removed from the output of each channel individually.
+	Channels,
+	/// DC offset is removed from the mixed output of the chip.
+	Master,
+}
+
+/// How quickly the amplitude and panning of a channel follow changes, to prevent clicks from abrupt changes.
+/// A rate or time of 0 makes changes instant.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Serialize, Deserialize)]
+#[repr(C)]
+pub enum Ramping {
+	/// Changes occur at a fixed rate in units/second, so larger changes take longer.
+	Rate(f32),
+	/// Changes occur over a fixed time in milliseconds, however large they are.
+	Time(f32),
+}
+
+impl Default for Ramping {
+	fn default() -> Self {
+		Ramping::Rate(channel::RAMPING_RATE)
+	}
+}
+
+/// Deserialized parameters haven't been rendered yet, so their EQ needs to be applied.
+fn eq_changed_default() -> bool {
+	true
 }
 
 /// Parameters detailing how an LSynth chip is intended to operate.
@@ -108,13 +381,34 @@ pub struct ChipParameters {
 	/// The samplerate in hertz.
 	samplerate: usize,
 	/// Seconds per sample
-	timestep: f32,
+	timestep: f64,
 	/// The global amplitude of this chip on a scale of 0..1. Affects all channels.
 	amplitude: f32,
+	/// The number of ticks per second. Double precision so that rates like 59.94 don't drift from the ideal grid of ticks.
+	tick_rate: f64,
 	/// The number of samples there are in a single tick.
-	tick_rate: f32,
-	/// The number of samples there are in a single tick.
-	tick_frames: f32,
+	tick_frames: f64,
+	/// Whether to add TPDF dither when generating audio in an integer format.
+	#[serde(default)]
+	dither: bool,
+	/// Where DC offset is removed from the output of the chip.
+	#[serde(default)]
+	dc_blocking: DcBlocking,
+	/// The bands of the parametric EQ applied to the output of the chip.
+	#[serde(default)]
+	eq: EqSettings,
+	/// Whether the EQ has changed since the chip last rendered, so that its filters only need to be updated when it does.
+	#[serde(skip, default = "eq_changed_default")]
+	eq_changed: bool,
+	/// How quickly the amplitude and panning of channels follow changes, unless overridden by a channel.
+	#[serde(default)]
+	ramping: Ramping,
+	/// The length of each tick in a repeating pattern, relative to the length set by the tick rate. Empty if every tick is the same length.
+	#[serde(default)]
+	groove: Vec<f32>,
+	/// Whether the chip queues events while generating audio, to be taken with `ChipState::poll_event`.
+	#[serde(default)]
+	events_enabled: bool,
 }
 
 /// Data returned by the generate function of ChipState.
@@ -126,49 +420,230 @@ pub struct ChipGenerationData {
 	pub remaining_samples: usize,
 }
 
+/// Checks that a wavetable has a valid length, and copies it with its samples clamped to -1..1
+fn validate_wavetable(wavetable: &[f32]) -> Result<Vec<f32>, LSynthError> {
+	waveform::validate_custom_length(wavetable.len())?;
+	Ok(wavetable.iter().map(|value| value.clamp(-1_f32, 1_f32)).collect())
+}
+
+/// A tick that the chip measures tick boundaries from, set whenever the tick rate or groove changes.
+struct TickAnchor {
+	/// The index of the tick.
+	tick: u64,
+	/// The frame the tick started on.
+	frame: u64,
+	/// The average number of frames in each tick from this one onwards.
+	tick_frames: f64,
+	/// The groove the anchor was set with, so that changes to it can be noticed.
+	groove: Vec<f32>,
+	/// The combined length of the ticks before each step of the normalized groove, relative to the average tick length.
+	/// Has one more element than the normalized groove, the last being exactly the number of steps in it.
+	groove_offsets: Vec<f64>,
+}
+
+impl TickAnchor {
+	/// Creates an anchor at the provided tick and frame, using the tick rate and groove of the provided parameters.
+	fn new(tick: u64, frame: u64, parameters: &ChipParameters) -> TickAnchor {
+		// Deserialized parameters haven't been through `ChipParameters::set_groove`, so the groove is normalized again here.
+		let lengths = normalize_groove(&parameters.groove);
+		let steps = lengths.len() as f64;
+		let mut groove_offsets = vec![0.0];
+		for length in lengths.iter() {
+			// Rounding mustn't let the offsets run past the end of the groove, or ticks could end before they start.
+			groove_offsets.push((groove_offsets[groove_offsets.len() - 1] + length).min(steps));
+		}
+		if let Some(last) = groove_offsets.last_mut() {
+			*last = steps;
+		}
+		
+		TickAnchor {
+			tick,
+			frame,
+			tick_frames: parameters.tick_frames,
+			groove: parameters.groove.clone(),
+			groove_offsets,
+		}
+	}
+	
+	/// Returns whether the anchor was set with the tick rate and groove of the provided parameters.
+	fn matches(&self, parameters: &ChipParameters) -> bool {
+		self.tick_frames == parameters.tick_frames && self.groove == parameters.groove
+	}
+	
+	/// Returns the combined length of every tick before the provided tick, relative to the average tick length,
+	/// as if the anchor's tick rate and groove had been used since the chip was created.
+	fn groove_position(&self, tick: u64) -> f64 {
+		let length = (self.groove_offsets.len() - 1) as u64;
+		if length == 0 { return tick as f64; }
+		
+		// Each full repeat of the groove is exactly as long as the same number of even ticks.
+		let step = tick % length;
+		(tick - step) as f64 + self.groove_offsets[step as usize]
+	}
+}
+
+/// Scales a groove so that its average length is exactly 1, leaving out lengths which aren't finite and treating negative lengths as 0.
+/// Returns an empty groove if none of the lengths are positive.
+fn normalize_groove(groove: &[f32]) -> Vec<f64> {
+	let groove: Vec<f64> = groove.iter()
+		.filter(|length| length.is_finite())
+		.map(|length| length.max(0.0) as f64)
+		.collect();
+	let total: f64 = groove.iter().sum();
+	
+	if total > 0.0 {
+		groove.iter().map(|length| length * groove.len() as f64 / total).collect()
+	}
+	else {
+		Vec::new()
+	}
+}
+
+/// Audio generated by the last render of the chip, before being written to an output buffer.
+/// Kept between renders so that generating audio doesn't allocate every time.
+#[derive(Default)]
+struct RenderBuffers {
+	/// The mixed and clipped output of the chip.
+	mix: Vec<(f32, f32)>,
+	/// The output of every channel mixed together, scaled by each channel's reverb send level.
+	reverb_sends: Vec<(f32, f32)>,
+	/// The output of each individual channel, scaled by the chip's amplitude but not clipped.
+	channels: Vec<Vec<(f32, f32)>>,
+}
+
 impl ChipParameters {
 	/// Creates a new set of chip parameters. Tick rate is ticks per second.
 	pub fn new(samplerate: usize, amplitude: f32, tick_rate: f32) -> ChipParameters {
+		ChipParameters::new_f64(samplerate, amplitude, tick_rate as f64)
+	}
+	
+	/// Identical to `ChipParameters::new`, but takes the tick rate in double precision.
+	pub fn new_f64(samplerate: usize, amplitude: f32, tick_rate: f64) -> ChipParameters {
 		ChipParameters {
 			samplerate,
-			timestep: 1.0/(samplerate as f32),
+			timestep: 1.0/(samplerate as f64),
 			amplitude,
 			tick_rate,
-			tick_frames: samplerate as f32 / tick_rate
+			tick_frames: samplerate as f64 / tick_rate,
+			dither: false,
+			dc_blocking: DcBlocking::Off,
+			eq: EqSettings::default(),
+			eq_changed: true,
+			ramping: Ramping::default(),
+			groove: Vec::new(),
+			events_enabled: false,
 		}
 	}
 	
 	/// Converts the from ticks per second to samples per tick.
 	fn update_tick_frames(&mut self) {
-	 	self.tick_frames = self.samplerate as f32 / self.tick_rate
+	 	self.tick_frames = self.samplerate as f64 / self.tick_rate
 	}
 	
 	/// Sets the samplerate of the chip in hertz.
 	pub fn set_sample_rate(&mut self, samplerate: usize) {
 		self.samplerate = samplerate;
-		self.timestep = 1.0/(samplerate as f32);
+		self.timestep = 1.0/(samplerate as f64);
 		self.update_tick_frames();
 	}
 	
 	/// Sets the tick rate of the chip in hertz.
 	pub fn set_tick_rate(&mut self, tick_rate: f32) {
+		self.set_tick_rate_f64(tick_rate as f64);
+	}
+	
+	/// Sets the tick rate of the chip in hertz, in double precision.
+	pub fn set_tick_rate_f64(&mut self, tick_rate: f64) {
 		self.tick_rate = tick_rate;
 		self.update_tick_frames();
 	}
 	
+	/// Returns the samplerate of the chip in hertz.
+	pub fn get_sample_rate(&self) -> usize {
+		self.samplerate
+	}
+	
 	/// Returns the number of samples in a single tick.
 	pub fn get_tick_frames(&self) -> f32 {
-	 	self.tick_frames
+	 	self.tick_frames as f32
+	}
+	
+	/// Returns the number of samples in a single tick, in the double precision used to place the ticks.
+	pub fn get_tick_frames_f64(&self) -> f64 {
+		self.tick_frames
+	}
+	
+	/// Sets whether the chip queues events while generating audio, to be taken with `ChipState::poll_event`. Events are not queued by default.
+	pub fn set_events_enabled(&mut self, events_enabled: bool) {
+		self.events_enabled = events_enabled;
+	}
+	
+	/// Sets whether TPDF dither is added when generating audio in an integer format. Has no effect on floating point formats.
+	pub fn set_dither(&mut self, dither: bool) {
+		self.dither = dither;
+	}
+	
+	/// Sets where DC offset is removed from the output of the chip.
+	pub fn set_dc_blocking(&mut self, dc_blocking: DcBlocking) {
+		self.dc_blocking = dc_blocking;
+	}
+	
+	/// Sets the bands of the parametric EQ applied to the output of the chip.
+	pub fn set_eq(&mut self, eq: EqSettings) {
+		self.eq = eq;
+		self.eq_changed = true;
+	}
+	
+	/// Returns the bands of the parametric EQ applied to the output of the chip.
+	pub fn get_eq(&self) -> &EqSettings {
+		&self.eq
+	}
+	
+	/// Sets a repeating pattern of tick lengths, relative to the length set by the tick rate.
+	/// The pattern is scaled so that its average length is 1, which keeps the overall tempo of the chip the same.
+	/// An empty pattern, or one with no positive lengths, makes every tick the same length again.
+	/// Lengths which aren't finite are left out, and negative lengths are treated as 0.
+	pub fn set_groove(&mut self, groove: &[f32]) {
+		self.groove = normalize_groove(groove).iter().map(|length| *length as f32).collect();
+	}
+	
+	/// Sets the groove to alternate between long and short ticks.
+	/// `swing` is the portion of each pair of ticks taken by the first tick as a percentage, where 50 makes every tick the same length,
+	/// and 66.7 gives a triplet feel.
+	pub fn set_swing(&mut self, swing: f32) {
+		let swing = swing.clamp(1.0, 99.0) / 100.0;
+		self.set_groove(&[swing, 1.0 - swing]);
+	}
+	
+	/// Returns the pattern of tick lengths, relative to the length set by the tick rate. Empty if every tick is the same length.
+	pub fn get_groove(&self) -> &[f32] {
+		&self.groove
+	}
+	
+	/// Sets how quickly the amplitude and panning of channels follow changes. Channels can override this with `Command::SetRamping`.
+	pub fn set_ramping(&mut self, ramping: Ramping) {
+		self.ramping = ramping;
 	}
 }
 
 impl ChipState {
 	/// Creates a new LSynth chip.
 	pub fn new(channel_count: usize, parameters: ChipParameters) -> ChipState {
+		let tick_anchor = TickAnchor::new(0, 0, &parameters);
+		
 		ChipState {
 			channels: (0..channel_count).map(|_| ChannelState::new()).collect(),
 			parameters,
-			remaining_frames: 0.0,
+			frames_rendered: 0,
+			tick_anchor,
+			pcm_samples: Vec::new(),
+			wavetables: Vec::new(),
+			master_bus: MasterBus::new(),
+			channel_dc_blockers: (0..channel_count).map(|_| DcBlocker::new()).collect(),
+			events: VecDeque::new(),
+			dropped_events: 0,
+			ticks: 0,
+			buffers: RenderBuffers::default(),
 		}
 	}
 	
@@ -178,62 +653,478 @@ impl ChipState {
 	/// 
 	/// If the number of remaining samples is anything but zero, then the tick was not completed.
 	/// Commands can still be sent at this point, but they will occur in between ticks.
+	/// 
+	/// Events such as slides finishing and ticks completing are queued while generating, and can be taken with `poll_event`.
 	pub fn generate(&mut self, buffer: &mut [f32]) -> Result<ChipGenerationData, LSynthError> {
-		use rayon::prelude::*;
-		
+		self.generate_as(buffer)
+	}
+	
+	/// Identical to `generate`, but writes the samples in any supported output format, such as `i16`, `u8`, `i32` or `f64`.
+	/// Integer formats are dithered if enabled in the chip's parameters.
+	// `usize::is_multiple_of` would raise the minimum supported compiler version.
+	#[allow(clippy::manual_is_multiple_of)]
+	pub fn generate_as<T: OutputSample>(&mut self, buffer: &mut [T]) -> Result<ChipGenerationData, LSynthError> {
 		if buffer.len() % 2 != 0 {
 			return Err(LSynthError::UnevenBufferSlice(UnevenBufferSliceError{slice_length: buffer.len()}));
 		}
 		
-		// Don't want to have to borrow this.
+		let frames = self.render(buffer.len() / 2);
+		let dither = self.parameters.dither;
+		
+		for (frame, (l, r)) in buffer.chunks_mut(2).zip(self.buffers.mix.iter()) {
+			frame[0] = format::convert(*l, dither);
+			frame[1] = format::convert(*r, dither);
+		}
+		
+		Ok(self.generation_data(frames, 2))
+	}
+	
+	/// Identical to `generate_as`, but writes the left and right channels to separate slices instead of interlacing them.
+	/// Both slices must be the same length.
+	/// 
+	/// The returned sample counts are per slice, so they are equal to the number of frames.
+	pub fn generate_planar<T: OutputSample>(&mut self, left: &mut [T], right: &mut [T]) -> Result<ChipGenerationData, LSynthError> {
+		if left.len() != right.len() {
+			return Err(LSynthError::MismatchedBufferLength(MismatchedBufferLengthError{expected_length: left.len(), slice_length: right.len()}));
+		}
+		
+		let frames = self.render(left.len());
+		let dither = self.parameters.dither;
+		
+		for ((l_out, r_out), (l, r)) in left.iter_mut().zip(right.iter_mut()).zip(self.buffers.mix.iter()) {
+			*l_out = format::convert(*l, dither);
+			*r_out = format::convert(*r, dither);
+		}
+		
+		Ok(self.generation_data(frames, 1))
+	}
+	
+	/// Identical to `generate_as`, but downmixes the stereo output to a single channel, writing one sample per frame.
+	pub fn generate_mono<T: OutputSample>(&mut self, buffer: &mut [T]) -> Result<ChipGenerationData, LSynthError> {
+		let frames = self.render(buffer.len());
+		let dither = self.parameters.dither;
+		
+		for (sample, (l, r)) in buffer.iter_mut().zip(self.buffers.mix.iter()) {
+			*sample = format::convert((l + r) * 0.5, dither);
+		}
+		
+		Ok(self.generation_data(frames, 1))
+	}
+	
+	/// Fills the whole of the provided slice with interlaced stereo samples in any supported output format,
+	/// calling `on_tick` every time a tick is completed. This is where commands should be sent to the chip.
+	/// 
+	/// `on_tick` is given the index of the tick that was just completed, counting from 0 when the chip was created.
+	pub fn render_with_ticks<T: OutputSample>(&mut self, buffer: &mut [T], on_tick: &mut impl FnMut(&mut ChipState, u64)) -> Result<(), LSynthError> {
+		let mut sample_index = 0;
+		
+		while sample_index < buffer.len() {
+			let generated_data = self.generate_as(&mut buffer[sample_index..])?;
+			sample_index += generated_data.generated;
+			
+			if generated_data.remaining_samples == 0 {
+				let tick_index = self.ticks - 1;
+				on_tick(self, tick_index);
+			}
+		}
+		
+		Ok(())
+	}
+	
+	/// Returns how many ticks the chip has completed since it was created.
+	pub fn tick_count(&self) -> u64 {
+		self.ticks
+	}
+	
+	/// Identical to `generate_as`, but writes the output of each channel to its own interlaced stereo slice instead of mixing them.
+	/// There must be exactly one stem slice per channel. The stems are scaled by the chip's amplitude, but are not clipped.
+	/// 
+	/// If `master` is provided, the usual mixed and clipped output is written to it as well.
+	/// All of the provided slices must be the same length.
+	#[allow(clippy::manual_is_multiple_of)]
+	pub fn generate_stems<T: OutputSample>(&mut self, stems: &mut [&mut [T]], mut master: Option<&mut [T]>) -> Result<ChipGenerationData, LSynthError> {
+		if stems.len() != self.channels.len() {
+			return Err(LSynthError::InvalidStemCount(InvalidStemCountError{stem_count: stems.len(), channel_count: self.channels.len()}));
+		}
+		
+		let length = match (stems.first(), &master) {
+			(Some(stem), _) => stem.len(),
+			(None, Some(master)) => master.len(),
+			(None, None) => 0,
+		};
+		
+		if length % 2 != 0 {
+			return Err(LSynthError::UnevenBufferSlice(UnevenBufferSliceError{slice_length: length}));
+		}
+		
+		for slice_length in stems.iter().map(|stem| stem.len()).chain(master.iter().map(|master| master.len())) {
+			if slice_length != length {
+				return Err(LSynthError::MismatchedBufferLength(MismatchedBufferLengthError{expected_length: length, slice_length}));
+			}
+		}
+		
+		let frames = self.render(length / 2);
+		let dither = self.parameters.dither;
+		
+		for (stem, channel_frames) in stems.iter_mut().zip(self.buffers.channels.iter()) {
+			for (frame, (l, r)) in stem.chunks_mut(2).zip(channel_frames.iter()) {
+				frame[0] = format::convert(*l, dither);
+				frame[1] = format::convert(*r, dither);
+			}
+		}
+		
+		if let Some(master) = master.as_mut() {
+			for (frame, (l, r)) in master.chunks_mut(2).zip(self.buffers.mix.iter()) {
+				frame[0] = format::convert(*l, dither);
+				frame[1] = format::convert(*r, dither);
+			}
+		}
+		
+		Ok(self.generation_data(frames, 2))
+	}
+	
+	/// Returns the number of channels operated by this chip.
+	pub fn channel_count(&self) -> usize {
+		self.channels.len()
+	}
+	
+	/// Reports how many samples were generated and how many remain in the tick, given how many samples make up a frame in the output layout.
+	fn generation_data(&self, frames_generated: usize, samples_per_frame: usize) -> ChipGenerationData {
+		ChipGenerationData {
+			generated: frames_generated * samples_per_frame,
+			remaining_samples: self.remaining_frames() as usize * samples_per_frame,
+		}
+	}
+	
+	/// Returns the frame that the provided tick ends on, counting ticks and frames from when the chip was created.
+	fn tick_boundary(&self, tick: u64) -> u64 {
+		let anchor = &self.tick_anchor;
+		let ticks = anchor.groove_position(tick) - anchor.groove_position(anchor.tick);
+		anchor.frame + (ticks * anchor.tick_frames).floor() as u64
+	}
+	
+	/// Moves the tick anchor to the current tick if the tick rate or groove has changed.
+	/// Changes to the tick rate and groove take effect from the start of the next tick.
+	fn update_tick_anchor(&mut self) {
+		if self.frames_rendered == self.tick_boundary(self.ticks) && !self.tick_anchor.matches(&self.parameters) {
+			self.tick_anchor = TickAnchor::new(self.ticks, self.frames_rendered, &self.parameters);
+		}
+	}
+	
+	/// Returns the frame at a fractional position in the ticks, counting from when the chip was created.
+	/// Fractions of a tick are spread evenly across that tick, so that the groove is followed.
+	fn tick_position_frame(&self, position: f64) -> f64 {
+		let tick = position.floor() as u64;
+		let start = self.tick_boundary(tick) as f64;
+		let end = self.tick_boundary(tick + 1) as f64;
+		start + (position - tick as f64) * (end - start)
+	}
+	
+	/// Converts a number of ticks from the current position of the chip to the time they take in milliseconds, following the groove.
+	fn ticks_to_milliseconds(&mut self, ticks: f32) -> f32 {
+		let ticks = ticks.max(0.0);
+		if ticks.is_infinite() { return f32::INFINITY; }
+		self.update_tick_anchor();
+		
+		let start = self.tick_boundary(self.ticks);
+		let end = self.tick_boundary(self.ticks + 1);
+		let progress = if end > start { (self.frames_rendered - start) as f64 / (end - start) as f64 } else { 0.0 };
+		let position = self.ticks as f64 + progress;
+		
+		// Limited so that the ticks can't overflow, which is still far longer than any slide could need.
+		let ticks = (ticks as f64).min(u32::MAX as f64);
+		let frames = self.tick_position_frame(position + ticks) - self.frames_rendered as f64;
+		(frames * self.parameters.timestep * 1000.0) as f32
+	}
+	
+	/// Returns how many frames are left in the current tick, or 0 if the last tick has ended and the next has yet to start.
+	fn remaining_frames(&self) -> u64 {
+		// The first tick starts when the chip is created, rather than at the end of an earlier tick.
+		if self.ticks > 0 && self.frames_rendered == self.tick_boundary(self.ticks) {
+			0
+		}
+		else {
+			self.tick_boundary(self.ticks + 1) - self.frames_rendered
+		}
+	}
+	
+	/// Generates up to `max_frames` stereo frames of audio into the chip's render buffers, stopping early if the tick ends.
+	/// Returns how many frames were generated.
+	fn render(&mut self, max_frames: usize) -> usize {
+		use rayon::prelude::*;
+		
+		// Don't want to have to borrow these.
 		let timestep = self.parameters.timestep;
+		let amplitude = self.parameters.amplitude;
+		let ramping = self.parameters.ramping;
+		
+		self.update_tick_anchor();
 		
-		if self.remaining_frames < 1.0 {
-			self.remaining_frames += self.parameters.get_tick_frames();
+		let tick_end = self.tick_boundary(self.ticks + 1);
+		let frames_to_generate = ((tick_end - self.frames_rendered) as usize).min(max_frames);
+		
+		// Taken so that the buffers can be filled while the channels are borrowed.
+		let mut buffers = std::mem::take(&mut self.buffers);
+		buffers.channels.resize_with(self.channels.len(), Vec::new);
+		for frames in buffers.channels.iter_mut() {
+			frames.clear();
 		}
+		let frame_vecs = &mut buffers.channels;
 		
-		let frames_to_generate = (self.remaining_frames.floor() as usize).min(buffer.len() / 2);
+		if self.channels.iter().any(|channel| channel.modulation_source().is_some()) {
+			self.render_dependent_channels(frame_vecs, frames_to_generate, timestep, amplitude, ramping);
+		}
+		else {
+			// Generate from each channel on its own thread.
+			self.channels.par_iter_mut().zip(frame_vecs.par_iter_mut())
+				.for_each(|(channel, frames)| {
+					frames.resize(frames_to_generate, (0.0, 0.0));
+					for value in frames.iter_mut() {
+						let (l, r) = channel.sample(0.0);
+						*value = (l * amplitude, r * amplitude);
+						channel.advance(timestep, ramping);
+					}
+				});
+		}
 		
-		// Generate from each channel on its own thread.
-		let frame_vecs: Vec<Vec<(f32, f32)>> = self.channels.par_iter_mut()
-			.map(|channel| {
-				let mut frames = vec![(0.0, 0.0); frames_to_generate];
-				for value in frames.iter_mut() {
-					*value = channel.sample();
-					channel.advance(timestep);
-				}
-				frames
-			})
-			.collect();
+		// Chorus and stereo width need the whole stereo output of the channel, so they're applied once it's been generated.
+		let parameters = &self.parameters;
+		self.channels.par_iter_mut().zip(frame_vecs.par_iter_mut())
+			.for_each(|(channel, frames)| channel.process_output(frames, parameters));
+		
+		if self.parameters.dc_blocking == DcBlocking::Channels {
+			for (frames, dc_blocker) in frame_vecs.iter_mut().zip(self.channel_dc_blockers.iter_mut()) {
+				dc_blocker.process(frames, &self.parameters);
+			}
+		}
 		
 		// Iterating over frame_vecs would give us access to one channel at a time, which is not helpful,
-		// so instead we're iterating over the slice of the buffer we intend to fill.
-		for (i, frame) in buffer.chunks_mut(2).enumerate() {
-			if i >= frames_to_generate { break; }
-			frame[0] = 0.0;
-			frame[1] = 0.0;
-			
-			for channel in frame_vecs.iter() {
-				let (l, r) = channel[i];
-				frame[0] += l * self.parameters.amplitude;
-				frame[1] += r * self.parameters.amplitude;
+		// so instead we're iterating over the frames we intend to fill.
+		let mix = &mut buffers.mix;
+		let reverb_sends = &mut buffers.reverb_sends;
+		mix.clear();
+		mix.resize(frames_to_generate, (0.0, 0.0));
+		reverb_sends.clear();
+		reverb_sends.resize(frames_to_generate, (0.0, 0.0));
+		for (i, (frame, reverb_send)) in mix.iter_mut().zip(reverb_sends.iter_mut()).enumerate() {
+			for (frames, channel) in frame_vecs.iter().zip(self.channels.iter()) {
+				let (l, r) = frames[i];
+				let send = channel.reverb_send();
+				frame.0 += l;
+				frame.1 += r;
+				reverb_send.0 += l * send;
+				reverb_send.1 += r * send;
 			}
-			
+		}
+		
+		self.master_bus.process(mix, reverb_sends, frame_vecs, &self.parameters);
+		self.parameters.eq_changed = false;
+		
+		for frame in mix.iter_mut() {
 			// Hard clip to prevent artifacts.
-			frame[0] = frame[0].clamp(-1.0, 1.0);
-			frame[1] = frame[1].clamp(-1.0, 1.0);
+			frame.0 = frame.0.clamp(-1.0, 1.0);
+			frame.1 = frame.1.clamp(-1.0, 1.0);
+		}
+		
+		self.frames_rendered += frames_to_generate as u64;
+		
+		for index in 0..self.channels.len() {
+			for event in self.channels[index].take_events() {
+				self.push_event(event.for_channel(index));
+			}
+		}
+		
+		if self.frames_rendered == tick_end {
+			for channel in self.channels.iter_mut() {
+				channel.tick();
+			}
+			self.ticks += 1;
+			self.push_event(ChipEvent::TickBoundary);
+		}
+		
+		self.buffers = buffers;
+		frames_to_generate
+	}
+	
+	/// Generates the output of every channel one frame at a time, for when some channels are modulated by others.
+	/// Modulators are generated before the channels they modulate. If channels modulate each other in a loop,
+	/// the loop is broken by using the modulator's output from the previous frame.
+	/// The frames of each channel are pushed onto its empty vector in `frame_vecs`.
+	fn render_dependent_channels(&mut self, frame_vecs: &mut [Vec<(f32, f32)>], frames: usize, timestep: f64, amplitude: f32, ramping: Ramping) {
+		let order = self.render_order();
+		let mut oscillators: Vec<f32> = self.channels.iter().map(|channel| channel.oscillator()).collect();
+		let mut wrapped = vec![false; self.channels.len()];
+		
+		for _ in 0..frames {
+			for &index in order.iter() {
+				let channel = &mut self.channels[index];
+				let source = channel.modulation_source();
+				
+				oscillators[index] = channel.oscillator();
+				let (l, r) = channel.sample(source.map_or(0.0, |source| oscillators[source]));
+				frame_vecs[index].push((l * amplitude, r * amplitude));
+				
+				wrapped[index] = channel.advance(timestep, ramping);
+				if let Some(source) = source {
+					channel.sync(wrapped[source]);
+				}
+			}
+		}
+	}
+	
+	/// Orders the channels so that every channel comes after the channel modulating it, except where they modulate each other in a loop.
+	fn render_order(&self) -> Vec<usize> {
+		let mut order = Vec::with_capacity(self.channels.len());
+		let mut visited = vec![false; self.channels.len()];
+		
+		for start in 0..self.channels.len() {
+			// Follows the chain of modulators until it reaches a channel that's already been ordered, or it loops back on itself.
+			let mut chain = Vec::new();
+			let mut current = Some(start);
+			while let Some(index) = current {
+				if visited[index] { break; }
+				visited[index] = true;
+				chain.push(index);
+				current = self.channels[index].modulation_source();
+			}
+			order.extend(chain.into_iter().rev());
 		}
 		
-		// Adds only the fractional part of tick_frames.
-		self.remaining_frames -= frames_to_generate as f32;
+		order
+	}
+	
+	/// Changes the custom waveform stored in the given channel.
+	/// Unlike `Command::SetCustomWaveform`, the waveform can be any power of two length between `waveform::MIN_CUSTOM_WIDTH` and `waveform::MAX_CUSTOM_WIDTH`.
+	pub fn set_custom_waveform(&mut self, waveform: &[f32], channel: usize) -> Result<(), LSynthError> {
+		self.channel_mut(channel)?.set_custom_waveform(waveform)
+	}
+	
+	/// Executes a command on the effects applied to the output of the whole chip.
+	pub fn send_master_command(&mut self, command: MasterCommand) -> Result<(), LSynthError> {
+		if let MasterCommand::SetCompressorSidechain(channel) = command {
+			if channel >= self.channels.len() {
+				return Err(LSynthError::InvalidChannel(InvalidChannelError {
+					attempted_channel: channel,
+					max_channels_of_chip: self.channels.len(),
+				}));
+			}
+		}
 		
-		Ok(ChipGenerationData {generated: frames_to_generate * 2, remaining_samples: (self.remaining_frames.floor() as usize) * 2})
+		self.master_bus.execute_command(command);
+		Ok(())
+	}
+	
+	/// Takes the oldest event reported while generating audio, if there are any waiting.
+	/// Events are only reported once they have been enabled with `ChipParameters::set_events_enabled`.
+	pub fn poll_event(&mut self) -> Option<ChipEvent> {
+		self.events.pop_front()
+	}
+	
+	/// Takes every event reported while generating audio, from oldest to newest.
+	pub fn drain_events(&mut self) -> impl Iterator<Item = ChipEvent> + '_ {
+		self.events.drain(..)
+	}
+	
+	/// Returns how many events are waiting to be taken with `ChipState::poll_event`.
+	pub fn event_count(&self) -> usize {
+		self.events.len()
+	}
+	
+	/// Returns how many events have been discarded since the chip was created, because more than `MAX_QUEUED_EVENTS` were waiting.
+	/// Events aren't counted while they are disabled.
+	pub fn dropped_event_count(&self) -> u64 {
+		self.dropped_events
+	}
+	
+	/// Adds an event to the queue, discarding the oldest event if the queue is full. Does nothing if events are disabled.
+	fn push_event(&mut self, event: ChipEvent) {
+		if !self.parameters.events_enabled { return; }
+		if self.events.len() >= MAX_QUEUED_EVENTS {
+			self.events.pop_front();
+			self.dropped_events += 1;
+		}
+		self.events.push_back(event);
+	}
+	
+	/// Loads a PCM sample into the chip, so that channels can play it back. Returns the index used to refer to the sample in commands.
+	pub fn load_pcm_sample(&mut self, sample: PcmSample) -> usize {
+		self.pcm_samples.push(Arc::new(sample));
+		self.pcm_samples.len() - 1
+	}
+	
+	/// Loads a wavetable into the chip's bank, so that channels can morph between it and other wavetables.
+	/// Returns the index used to refer to the wavetable in commands.
+	/// 
+	/// Wavetables have the same length requirements as custom waveforms set with `set_custom_waveform`.
+	pub fn load_wavetable(&mut self, wavetable: &[f32]) -> Result<usize, LSynthError> {
+		self.wavetables.push(Arc::new(validate_wavetable(wavetable)?));
+		Ok(self.wavetables.len() - 1)
+	}
+	
+	/// Replaces a wavetable in the chip's bank. Channels using the wavetable will use the new one immediately.
+	pub fn replace_wavetable(&mut self, index: usize, wavetable: &[f32]) -> Result<(), LSynthError> {
+		let wavetable = Arc::new(validate_wavetable(wavetable)?);
+		*self.wavetable(index)? = wavetable.clone();
+		
+		for channel in self.channels.iter_mut() {
+			channel.refresh_wavetable(index, &wavetable);
+		}
+		Ok(())
+	}
+	
+	/// Returns the wavetable in the chip's bank with the given index, or an error if it doesn't exist.
+	fn wavetable(&mut self, index: usize) -> Result<&mut Arc<Vec<f32>>, LSynthError> {
+		let loaded_wavetables = self.wavetables.len();
+		self.wavetables.get_mut(index).ok_or(LSynthError::InvalidWavetable(InvalidWavetableError {
+			attempted_wavetable: index,
+			loaded_wavetables,
+		}))
+	}
+	
+	/// Returns the channel with the given index, or an error if it doesn't exist.
+	fn channel_mut(&mut self, channel: usize) -> Result<&mut ChannelState, LSynthError> {
+		let max_channels_of_chip = self.channels.len();
+		self.channels.get_mut(channel).ok_or(LSynthError::InvalidChannel(InvalidChannelError {
+			max_channels_of_chip,
+			attempted_channel: channel,
+		}))
 	}
 	
 	/// Executes a command on the given channel.
 	pub fn send_command(&mut self, command: Command, channel: usize) -> Result<(), LSynthError> {
 		if channel < self.channels.len() {
-			self.channels[channel].execute_command(command)?;
+			match command {
+				Command::SetModulation(_, source) if source >= self.channels.len() => {
+					return Err(LSynthError::InvalidChannel(InvalidChannelError {
+						max_channels_of_chip: self.channels.len(),
+						attempted_channel: source,
+					}));
+				}
+				Command::SetPcmSample(index) => {
+					let sample = self.pcm_samples.get(index).ok_or(LSynthError::InvalidPcmSample(InvalidPcmSampleError {
+						attempted_sample: index,
+						loaded_samples: self.pcm_samples.len(),
+					}))?;
+					self.channels[channel].set_pcm_sample(sample.clone());
+				}
+				Command::SetWavetables(first, second) => {
+					let wavetables = [self.wavetable(first)?.clone(), self.wavetable(second)?.clone()];
+					self.channels[channel].set_wavetables(wavetables, [first, second]);
+				}
+				Command::FrequencySlideTicks(value, ticks) => {
+					let time = self.ticks_to_milliseconds(ticks);
+					self.channels[channel].execute_command(Command::FrequencySlideTime(value, time))?;
+				}
+				Command::AmplitudeSlideTicks(value, ticks) => {
+					let time = self.ticks_to_milliseconds(ticks);
+					self.channels[channel].execute_command(Command::AmplitudeSlideTime(value, time))?;
+				}
+				Command::PanningSlideTicks(value, ticks) => {
+					let time = self.ticks_to_milliseconds(ticks);
+					self.channels[channel].execute_command(Command::PanningSlideTime(value, time))?;
+				}
+				command => self.channels[channel].execute_command(command)?,
+			}
 			Ok(())
 		}
 		else {
@@ -243,4 +1134,104 @@ impl ChipState {
 			}))
 		}
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	/// Reads the tag of a command, which is what C callers write when they pass a `Command` by value.
+	fn command_tag(command: &Command) -> u32 {
+		// A `#[repr(C)]` enum with fields starts with a C-like enum tag.
+		unsafe { *(command as *const Command as *const u32) }
+	}
+	
+	/// The commands which existed before any were added must keep their discriminants, or C callers would send the wrong commands.
+	#[test]
+	fn original_command_discriminants_are_unchanged() {
+		let commands = [
+			Command::SetWaveform(0),
+			Command::SetFrequency(0.0),
+			Command::SetAmplitude(0.0),
+			Command::SetPanning(0.0),
+			Command::SetCustomWaveform([0.0; waveform::CUSTOM_WIDTH]),
+			Command::SetPhase(0.0),
+			Command::ForceSetAmplitude(0.0),
+			Command::ForceSetPanning(0.0),
+			Command::FrequencySlide(0.0, 0.0),
+			Command::AmplitudeSlide(0.0, 0.0),
+			Command::PanningSlide(0.0, 0.0),
+		];
+		
+		for (expected, command) in commands.iter().enumerate() {
+			assert_eq!(command_tag(command), expected as u32);
+		}
+	}
+	
+	/// Every tick should be reported exactly once and in order, however the buffers line up with the ticks.
+	#[test]
+	fn render_with_ticks_reports_each_tick_once() {
+		for buffer_frames in [1, 97, 512, 733, 1000, 4096] {
+			let mut chip = ChipState::new(1, ChipParameters::new(44_100, 0.5, 60.1));
+			let mut buffer = vec![0_f32; buffer_frames * 2];
+			let mut ticks = Vec::new();
+			
+			while chip.frames_rendered < 20_000 {
+				chip.render_with_ticks(&mut buffer, &mut |chip: &mut ChipState, tick| {
+					assert_eq!(chip.frames_rendered, chip.tick_boundary(tick + 1));
+					ticks.push(tick);
+				}).unwrap();
+			}
+			
+			let expected = (1..).take_while(|&tick| chip.tick_boundary(tick) <= chip.frames_rendered).count() as u64;
+			assert_eq!(ticks, (0..expected).collect::<Vec<_>>(), "buffer of {} frames", buffer_frames);
+		}
+	}
+	
+	/// Finds the frame a tick ends on with integer arithmetic, using the exact value of the tick rate.
+	fn ideal_tick_boundary(tick: u64, samplerate: u64, tick_rate: f64) -> u64 {
+		// A positive, normal f64 is exactly mantissa * 2^exponent.
+		let bits = tick_rate.to_bits();
+		let exponent = ((bits >> 52) & 0x7ff) as i32 - 1075;
+		let mantissa = ((bits & ((1 << 52) - 1)) | (1 << 52)) as u128;
+		
+		let frames = tick as u128 * samplerate as u128;
+		let boundary = if exponent < 0 {
+			(frames << -exponent) / mantissa
+		}
+		else {
+			frames / (mantissa << exponent)
+		};
+		boundary as u64
+	}
+	
+	/// Ticks shouldn't drift from where the tick rate puts them, even after an hour.
+	#[test]
+	fn tick_boundaries_do_not_drift() {
+		let samplerate = 44_100;
+		let tick_rate = 59.94;
+		let mut chip = ChipState::new(0, ChipParameters::new_f64(samplerate, 0.5, tick_rate));
+		
+		// Rendering a whole hour would be too slow, so the chip is moved to the end of the hour's last tick.
+		let hour = (3600.0 * tick_rate) as u64;
+		chip.ticks = hour;
+		chip.frames_rendered = ideal_tick_boundary(hour, samplerate as u64, tick_rate);
+		
+		let mut buffer = vec![0_f32; 1000];
+		let mut ticks = 0;
+		while ticks < 600 {
+			chip.render_with_ticks(&mut buffer, &mut |chip: &mut ChipState, tick| {
+				assert_eq!(chip.frames_rendered, ideal_tick_boundary(tick + 1, samplerate as u64, tick_rate), "tick {}", tick);
+				ticks += 1;
+			}).unwrap();
+		}
+	}
+	
+	/// A new chip hasn't completed a tick yet, so the whole of the first tick should be left.
+	#[test]
+	fn first_tick_is_remaining_before_generating() {
+		let mut chip = ChipState::new(1, ChipParameters::new(48_000, 0.5, 60.0));
+		let data = chip.generate(&mut []).unwrap();
+		assert_eq!(data.remaining_samples, 800 * 2);
+	}
+}
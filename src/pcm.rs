@@ -0,0 +1,155 @@
+//! Contains the PCM samples that can be loaded into a chip, and the state channels use to play them back.
+
+use std::sync::Arc;
+use crate::errors::{LSynthError, InvalidLoopPointsError, InvalidPcmPitchError};
+
+/// A recording of mono audio that channels can play back, pitched by the frequency of the channel.
+pub struct PcmSample {
+	/// The samples of the recording on a scale of -1..1
+	data: Vec<f32>,
+	/// The samplerate of the recording in hertz.
+	samplerate: f32,
+	/// The frequency that a channel must be set to for the recording to play back at its original pitch.
+	base_frequency: f32,
+	/// The first sample of the looping section, when looping.
+	loop_start: usize,
+	/// The sample after the end of the looping section, when looping.
+	loop_end: usize,
+	/// Whether playback loops between the loop points, rather than stopping at the end of the recording.
+	looping: bool,
+}
+
+impl PcmSample {
+	/// Creates a new sample from mono audio, which plays once and then stops.
+	/// `base_frequency` is the channel frequency in hertz at which the recording plays at its original pitch.
+	/// Returns an error if the samplerate is 0, or the base frequency isn't positive and finite.
+	pub fn new(mut data: Vec<f32>, samplerate: usize, base_frequency: f32) -> Result<PcmSample, LSynthError> {
+		if samplerate == 0 || !base_frequency.is_finite() || base_frequency <= 0.0 {
+			return Err(LSynthError::InvalidPcmPitch(InvalidPcmPitchError {
+				samplerate,
+				base_frequency,
+			}));
+		}
+		
+		for value in data.iter_mut() {
+			*value = value.clamp(-1_f32, 1_f32);
+		}
+		
+		Ok(PcmSample {
+			loop_end: data.len(),
+			data,
+			samplerate: samplerate as f32,
+			base_frequency,
+			loop_start: 0,
+			looping: false,
+		})
+	}
+	
+	/// Makes playback repeat the section from `loop_start` up to, but not including, `loop_end` once it reaches it.
+	pub fn set_loop(&mut self, loop_start: usize, loop_end: usize) -> Result<(), LSynthError> {
+		if loop_start >= loop_end || loop_end > self.data.len() {
+			return Err(LSynthError::InvalidLoopPoints(InvalidLoopPointsError {
+				loop_start,
+				loop_end,
+				sample_length: self.data.len(),
+			}));
+		}
+		
+		self.loop_start = loop_start;
+		self.loop_end = loop_end;
+		self.looping = true;
+		Ok(())
+	}
+	
+	/// Makes playback stop once it reaches the end of the recording.
+	pub fn set_one_shot(&mut self) {
+		self.looping = false;
+	}
+	
+	/// Returns the number of samples in the recording.
+	pub fn len(&self) -> usize {
+		self.data.len()
+	}
+	
+	/// Returns whether the recording contains no samples.
+	pub fn is_empty(&self) -> bool {
+		self.data.is_empty()
+	}
+}
+
+/// The state of a channel playing back a PCM sample.
+pub(crate) struct PcmPlayer {
+	/// The sample being played, if one has been set.
+	sample: Option<Arc<PcmSample>>,
+	/// The position of playback within the recording, measured in samples of the recording.
+	/// Double precision so that long recordings don't lose their fractional position.
+	position: f64,
+	/// Whether the sample is still playing. One-shot samples stop at the end of the recording.
+	playing: bool,
+}
+
+impl PcmPlayer {
+	/// Creates a new player with no sample.
+	pub(crate) fn new() -> PcmPlayer {
+		PcmPlayer {
+			sample: None,
+			position: 0.0,
+			playing: false,
+		}
+	}
+	
+	/// Replaces the sample being played, and starts playing it from the beginning.
+	pub(crate) fn set_sample(&mut self, sample: Arc<PcmSample>) {
+		self.sample = Some(sample);
+		self.position = 0.0;
+		self.playing = true;
+	}
+	
+	/// Samples the recording at the current position, interpolating linearly between its samples.
+	pub(crate) fn sample(&self) -> f32 {
+		let sample = match &self.sample {
+			Some(sample) if self.playing && !sample.is_empty() => sample,
+			_ => return 0.0,
+		};
+		
+		let index = self.position.floor() as usize;
+		let fraction = (self.position - index as f64) as f32;
+		let current = sample.data[index.min(sample.data.len() - 1)];
+		
+		// When looping, the sample after the end of the loop is the start of the loop.
+		let next_index = if sample.looping && index + 1 >= sample.loop_end { sample.loop_start } else { index + 1 };
+		let next = sample.data.get(next_index).copied().unwrap_or(0.0);
+		
+		current + (next - current) * fraction
+	}
+	
+	/// Returns whether a sample is still being played.
+	pub(crate) fn is_playing(&self) -> bool {
+		self.playing
+	}
+	
+	/// Updates the playback position by the provided timestep in seconds, given the frequency of the channel in hertz.
+	pub(crate) fn advance(&mut self, frequency: f32, step: f64) {
+		let sample = match &self.sample {
+			Some(sample) if self.playing => sample,
+			_ => return,
+		};
+		
+		self.position += (frequency as f64 / sample.base_frequency as f64 * sample.samplerate as f64) * step;
+		
+		// An unreasonably high frequency can push the position beyond any point in the recording.
+		if !self.position.is_finite() {
+			self.playing = false;
+		}
+		else if sample.looping {
+			if self.position >= sample.loop_end as f64 {
+				let loop_start = sample.loop_start as f64;
+				let loop_length = (sample.loop_end - sample.loop_start) as f64;
+				self.position = loop_start + (self.position - loop_start).rem_euclid(loop_length);
+			}
+		}
+		else if self.position >= sample.data.len() as f64 {
+			self.playing = false;
+		}
+	}
+}
@@ -0,0 +1,130 @@
+//! Contains the slides that channels use to gradually change their parameters.
+
+/// The number of parameters of a channel that can slide.
+pub const SLIDE_TARGETS: usize = 3;
+/// The slide target for frequency.
+pub(crate) const SLIDE_FREQUENCY: usize = 0;
+/// The slide target for amplitude.
+pub(crate) const SLIDE_AMPLITUDE: usize = 1;
+/// The slide target for panning.
+pub(crate) const SLIDE_PANNING: usize = 2;
+
+/// The slide curve where the value changes at a constant rate.
+pub const SLIDE_LINEAR: usize = 0;
+/// The slide curve where the value changes by a constant ratio, which makes frequency slides move at a constant rate in pitch.
+/// If either end of the slide is 0, or they have different signs, the value starts slowly and speeds up instead.
+pub const SLIDE_EXPONENTIAL: usize = 1;
+/// The slide curve where the value starts quickly and slows down as it reaches the target, which makes fades end smoothly.
+pub const SLIDE_LOGARITHMIC: usize = 2;
+/// The slide curve where the value starts and ends slowly, and is fastest halfway through.
+pub const SLIDE_S_CURVE: usize = 3;
+
+/// How sharply the exponential and logarithmic curves bend when they can't change the value by a constant ratio.
+const CURVE_STEEPNESS: f32 = 4.0;
+
+/// The state of a single parameter sliding towards a target.
+pub(crate) struct Slide {
+	/// The value the slide started from.
+	start: f32,
+	/// The value the slide is approaching.
+	target: f32,
+	/// The time in seconds the slide takes to reach its target. Infinite if the slide never moves.
+	duration: f32,
+	/// The time in seconds since the slide started.
+	elapsed: f32,
+	/// The shape of the slide, using the `SLIDE_*` curve constants.
+	curve: usize,
+	/// Whether the slide has reached its target without that having been taken by `Slide::take_finished`.
+	finished: bool,
+}
+
+impl Slide {
+	/// Creates a new slide which rests at the provided value.
+	pub(crate) fn new(value: f32) -> Slide {
+		Slide {
+			start: value,
+			target: value,
+			duration: 0.0,
+			elapsed: 0.0,
+			curve: SLIDE_LINEAR,
+			finished: false,
+		}
+	}
+	
+	/// Stops the slide at the provided value.
+	pub(crate) fn set(&mut self, value: f32) {
+		*self = Slide { curve: self.curve, ..Slide::new(value) };
+	}
+	
+	/// Sets the shape of the slide. Takes effect on slides which are already in progress.
+	pub(crate) fn set_curve(&mut self, curve: usize) {
+		self.curve = curve;
+	}
+	
+	/// Starts sliding from the provided value towards the target, at an average rate in units/second.
+	/// A rate of 0 leaves the value where it is.
+	pub(crate) fn start_rate(&mut self, from: f32, target: f32, rate: f32) {
+		let rate = rate.abs();
+		let duration = if rate > 0.0 { (target - from).abs() / rate } else { f32::INFINITY };
+		self.start_time(from, target, duration);
+	}
+	
+	/// Starts sliding from the provided value towards the target, reaching it after the provided time in seconds.
+	pub(crate) fn start_time(&mut self, from: f32, target: f32, duration: f32) {
+		self.start = from;
+		self.target = target;
+		self.duration = duration.max(0.0);
+		self.elapsed = 0.0;
+		// A slide with no duration never advances, so it has to be marked as finished straight away.
+		self.finished = self.duration == 0.0;
+	}
+	
+	/// Returns whether the slide has yet to reach its target.
+	pub(crate) fn is_active(&self) -> bool {
+		self.elapsed < self.duration
+	}
+	
+	/// Advances the slide by the provided timestep in seconds, and returns the value it has reached.
+	pub(crate) fn advance(&mut self, step: f32) -> f32 {
+		if !self.is_active() { return self.target; }
+		
+		self.elapsed = (self.elapsed + step).min(self.duration);
+		if !self.is_active() {
+			self.finished = true;
+		}
+		self.value()
+	}
+	
+	/// Returns whether the slide has reached its target since this was last called.
+	pub(crate) fn take_finished(&mut self) -> bool {
+		std::mem::replace(&mut self.finished, false)
+	}
+	
+	/// Returns the current value of the slide.
+	fn value(&self) -> f32 {
+		if !self.is_active() { return self.target; }
+		if self.duration.is_infinite() { return self.start; }
+		
+		let progress = self.elapsed / self.duration;
+		let geometric = self.start * self.target > 0.0;
+		match self.curve {
+			SLIDE_EXPONENTIAL if geometric => self.start * (self.target / self.start).powf(progress),
+			curve => self.start + (self.target - self.start) * shape(curve, progress),
+		}
+	}
+}
+
+/// Reshapes the progress of a slide on a scale of 0..1 according to the provided curve.
+fn shape(curve: usize, progress: f32) -> f32 {
+	/// Curves upwards, starting slowly and speeding up.
+	fn exponential(progress: f32) -> f32 {
+		(CURVE_STEEPNESS * progress).exp_m1() / CURVE_STEEPNESS.exp_m1()
+	}
+	
+	match curve {
+		SLIDE_EXPONENTIAL => exponential(progress),
+		SLIDE_LOGARITHMIC => 1.0 - exponential(1.0 - progress),
+		SLIDE_S_CURVE => progress * progress * (3.0 - 2.0 * progress),
+		_ => progress,
+	}
+}
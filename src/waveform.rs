@@ -1,11 +1,33 @@
 //! Contains the formulas for generating all the different types of waveforms. All generated samples are between -1 and 1, and the provided periods are expected to be between 0 and 1.
 
-/// The number of samples in a custom waveform.
+use crate::errors::{LSynthError, InvalidWaveformLengthError};
+
+/// The number of samples in a custom waveform sent with `Command::SetCustomWaveform`.
 pub const CUSTOM_WIDTH: usize = 32;
+/// The fewest samples a custom waveform can contain.
+pub const MIN_CUSTOM_WIDTH: usize = 16;
+/// The most samples a custom waveform can contain.
+pub const MAX_CUSTOM_WIDTH: usize = 4096;
 
 /// Custom waveforms only need to contain an array of data. This is a convenience type for arrays that follow the required pattern.
 pub type CustomWaveform = [f32; CUSTOM_WIDTH];
 
+/// The number of harmonics that can be sent with `Command::SetHarmonics`.
+pub const HARMONIC_COUNT: usize = 16;
+/// The number of samples in the custom waveforms built by `Command::SetHarmonics`.
+/// This is high enough that all of the harmonics are represented without aliasing.
+pub const HARMONIC_WIDTH: usize = 256;
+
+/// The amplitudes or phases of each harmonic of a waveform, starting from the fundamental. This is a convenience type for arrays that follow the required pattern.
+pub type Harmonics = [f32; HARMONIC_COUNT];
+
+/// Custom waveforms are sampled by using the value of the nearest sample before the period.
+pub(crate) const INTERPOLATION_NEAREST: usize = 0;
+/// Custom waveforms are sampled by blending linearly between the samples on either side of the period.
+pub(crate) const INTERPOLATION_LINEAR: usize = 1;
+/// Custom waveforms are sampled by fitting a Catmull-Rom spline through the four samples surrounding the period.
+pub(crate) const INTERPOLATION_CUBIC: usize = 2;
+
 /// Generates a sinewave
 pub(crate) fn sine(period: f32) -> f32 {
 	f32::sin(period * std::f32::consts::TAU)
@@ -37,9 +59,9 @@ pub(crate) fn square(period: f32) -> f32 {
 	else {-1.0}
 }
 
-/// Generates a pulse wave with a duty of 25%.
-pub(crate) fn pulse(period: f32) -> f32 {
-	if period < 0.25 {1.0}
+/// Generates a pulse wave with the provided duty on a scale of 0..1
+pub(crate) fn pulse(period: f32, width: f32) -> f32 {
+	if period < width {1.0}
 	else {-1.0}
 }
 
@@ -48,8 +70,93 @@ pub(crate) fn noise() -> f32 {
 	rand::random::<f32>() * 2.0 - 1.0
 }
 
-/// Samples a custom waveform at the given point in the period.
-pub(crate) fn custom(period: f32, data: &CustomWaveform) -> f32{
-	let index = (period * CUSTOM_WIDTH as f32).floor() as usize;
-	data[index]
+/// Samples a custom waveform at the given point in the period, using the given interpolation mode. The data is expected to have a valid length.
+pub(crate) fn custom(period: f32, data: &[f32], interpolation: usize) -> f32 {
+	let width = data.len();
+	let position = period * width as f32;
+	// The period should never reach 1, but float rounding could make the index reach the width.
+	let index = (position.floor() as usize).min(width - 1);
+	let fraction = position - index as f32;
+	
+	// The waveform repeats, so samples past the ends wrap around.
+	let at = |offset: isize| data[(index as isize + offset).rem_euclid(width as isize) as usize];
+	
+	match interpolation {
+		INTERPOLATION_LINEAR => {
+			let (current, next) = (at(0), at(1));
+			current + (next - current) * fraction
+		}
+		INTERPOLATION_CUBIC => {
+			let (previous, current, next, after) = (at(-1), at(0), at(1), at(2));
+			let a = -0.5 * previous + 1.5 * current - 1.5 * next + 0.5 * after;
+			let b = previous - 2.5 * current + 2.0 * next - 0.5 * after;
+			let c = -0.5 * previous + 0.5 * next;
+			(((a * fraction + b) * fraction + c) * fraction + current).clamp(-1.0, 1.0)
+		}
+		_ => at(0),
+	}
+}
+
+/// Returns an error if a custom waveform of the given length can't be used.
+/// Custom waveforms must have a power of two length between `MIN_CUSTOM_WIDTH` and `MAX_CUSTOM_WIDTH`.
+pub(crate) fn validate_custom_length(length: usize) -> Result<(), LSynthError> {
+	if !length.is_power_of_two() || !(MIN_CUSTOM_WIDTH..=MAX_CUSTOM_WIDTH).contains(&length) {
+		return Err(LSynthError::InvalidWaveformLength(InvalidWaveformLengthError {
+			attempted_length: length,
+		}));
+	}
+	Ok(())
+}
+
+/// Builds a custom waveform of the given length by adding together sinewaves at each harmonic of the fundamental.
+/// `amplitudes` and `phases` start from the fundamental, and phases are on a scale of 0..1
+/// Missing phases are treated as 0. If the result would exceed -1..1, it is scaled down to fit.
+pub fn from_harmonics(amplitudes: &[f32], phases: &[f32], length: usize) -> Vec<f32> {
+	let mut data: Vec<f32> = (0..length).map(|i| {
+		let period = i as f32 / length as f32;
+		amplitudes.iter().enumerate()
+			.map(|(harmonic, amplitude)| {
+				let phase = phases.get(harmonic).copied().unwrap_or(0.0);
+				amplitude * sine(period * (harmonic + 1) as f32 + phase)
+			})
+			.sum()
+	}).collect();
+	
+	let peak = data.iter().fold(0.0_f32, |peak, value| peak.max(value.abs()));
+	if peak > 1.0 {
+		for value in data.iter_mut() {
+			*value /= peak;
+		}
+	}
+	data
+}
+
+/// Builds a custom waveform of the size used by `Command::SetCustomWaveform` from harmonics, as described in `from_harmonics`.
+pub fn custom_from_harmonics(amplitudes: &[f32], phases: &[f32]) -> CustomWaveform {
+	let mut waveform = [0.0; CUSTOM_WIDTH];
+	waveform.copy_from_slice(&from_harmonics(amplitudes, phases, CUSTOM_WIDTH));
+	waveform
+}
+
+/// Analyzes a drawn waveform, returning the amplitudes and phases of its first `count` harmonics in the form used by `from_harmonics`.
+/// Harmonics above half the length of the waveform can't be represented by it, and are always 0.
+pub fn to_harmonics(data: &[f32], count: usize) -> (Vec<f32>, Vec<f32>) {
+	let length = data.len();
+	(1..=count).map(|harmonic| {
+		if length == 0 || harmonic * 2 > length { return (0.0, 0.0); }
+		
+		// Correlates the waveform against a sine and cosine at the harmonic's frequency.
+		let (sine_sum, cosine_sum) = data.iter().enumerate().fold((0.0, 0.0), |(sine_sum, cosine_sum), (i, value)| {
+			let angle = (i * harmonic) as f32 / length as f32 * std::f32::consts::TAU;
+			(sine_sum + value * angle.sin(), cosine_sum + value * angle.cos())
+		});
+		
+		// The Nyquist harmonic only has a single sample per half cycle, so it isn't doubled.
+		let scale = if harmonic * 2 == length { 1.0 } else { 2.0 } / length as f32;
+		let (sine_part, cosine_part) = (sine_sum * scale, cosine_sum * scale);
+		let amplitude = sine_part.hypot(cosine_part);
+		let phase = (cosine_part.atan2(sine_part) / std::f32::consts::TAU).rem_euclid(1.0);
+		// Tiny negative phases can round up to 1 when wrapped.
+		(amplitude, if phase >= 1.0 { 0.0 } else { phase })
+	}).unzip()
 }
\ No newline at end of file